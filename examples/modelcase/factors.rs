@@ -53,7 +53,7 @@ fn octaved_perlin(perlin: &Perlin, x: f64, y: f64, octaves: usize, persistence:
 pub fn calculate_population_density(
     terrain: &Terrain2D,
     graph: &EdgeAttributedUndirectedGraph<f64>,
-    is_outlet: &Vec<bool>,
+    is_outlet: &[bool],
 ) -> Vec<f64> {
     let max_slope_livable = std::f64::consts::PI / 3.0;
     let slopes = (0..terrain.sites().len())
@@ -79,7 +79,7 @@ pub fn calculate_population_density(
             }
             let slope_sum = slopes[i].iter().fold(0.0, |acc, slope| acc + slope.abs());
             let slope_avg = slope_sum.abs() / slopes[i].len() as f64;
-            (1.0 - slope_avg / max_slope_livable).max(0.0).min(1.0)
+            (1.0 - slope_avg / max_slope_livable).clamp(0.0, 1.0)
         })
         .collect::<Vec<_>>();
 
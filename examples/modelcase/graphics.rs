@@ -9,6 +9,7 @@ use tiny_skia::{Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
 
 use crate::map_provider::into_fastlem_site;
 
+#[allow(clippy::too_many_arguments)]
 pub fn write_to_image(
     bound_min: Site,
     bound_max: Site,
@@ -16,7 +17,7 @@ pub fn write_to_image(
     img_height: u32,
     terrain: &Terrain2D,
     network: &PathNetwork<TransportNode>,
-    population_densities: &Vec<f64>,
+    population_densities: &[f64],
     filename: &str,
 ) {
     let sites = terrain.sites();
@@ -41,7 +42,7 @@ pub fn write_to_image(
                 let site = Site { x, y };
                 let elevation = terrain.get_elevation(&into_fastlem_site(site));
                 let population_density = interpolator.interpolate(
-                    &population_densities,
+                    population_densities,
                     naturalneighbor::Point {
                         x: site.x,
                         y: site.y,
@@ -74,7 +75,7 @@ pub fn write_to_image(
 
     network.nodes_iter().for_each(|(inode_id, inode)| {
         // draw node
-        network.neighbors_iter(inode_id).map(|neighbors_iter| {
+        if let Some(neighbors_iter) = network.neighbors_iter(inode_id) {
             neighbors_iter.for_each(|(_, jnode)| {
                 let width = if inode.path_stage(jnode).as_num() == 0 {
                     2.0
@@ -110,7 +111,7 @@ pub fn write_to_image(
                 paint.set_color_rgba8(color[0], color[1], color[2], 100);
                 pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
             })
-        });
+        }
     });
 
     pixmap.save_png(filename).unwrap();
@@ -7,7 +7,7 @@ use street_engine::{
             priority::PathPrioritizationFactors,
             rules::{
                 branch::BranchRules, bridge::BridgeRules, direction::PathDirectionRules,
-                ElevationDiffLimit, TransportRules,
+                ElevationDiffLimit, GrowthMode, TransportRules,
             },
         },
         traits::{PathPrioritizator, TransportRulesProvider},
@@ -16,10 +16,12 @@ use street_engine::{
 
 use crate::map_provider::{into_fastlem_site, MapProvider};
 
+#[allow(dead_code)]
 pub struct RulesProviderForRailway<'a> {
     map_provider: &'a MapProvider<'a>,
 }
 
+#[allow(dead_code)]
 impl RulesProviderForRailway<'_> {
     pub fn new<'a>(map_provider: &'a MapProvider<'a>) -> RulesProviderForRailway<'a> {
         RulesProviderForRailway { map_provider }
@@ -31,7 +33,7 @@ impl<'a> TransportRulesProvider for RulesProviderForRailway<'a> {
         let population_density = self.map_provider.get_population_density(site)?;
         let path_normal_length = 0.7;
 
-        let branch_motivation = if metrics.extend_count_since_last_branched % 7 == 0
+        let branch_motivation = if metrics.extend_count_since_last_branched.is_multiple_of(7)
             && metrics.extend_count_since_last_branched > 7
         {
             1.0
@@ -41,21 +43,33 @@ impl<'a> TransportRulesProvider for RulesProviderForRailway<'a> {
 
         Some(TransportRules {
             path_normal_length,
+            path_length_jitter: 0.0,
             path_extra_length_for_intersection: path_normal_length * 0.7,
             path_slope_elevation_diff_limit: ElevationDiffLimit::Linear(10.0),
             path_grade_separation_elevation_diff_threshold: f64::MAX,
             branch_rules: BranchRules {
                 branch_density: (0.3 + population_density * 0.2) * branch_motivation,
                 staging_probability: 0.0,
+                branch_offset: 0.0,
+                termination_probability: 0.0,
             },
+            forbid_branch: false,
             path_direction_rules: PathDirectionRules {
                 max_radian: std::f64::consts::PI / (10.0 + 50.0 * population_density),
                 comparison_step: 3,
+                ..Default::default()
             },
             bridge_rules: BridgeRules {
                 max_bridge_length: 8.0,
+                min_bridge_length: 0.0,
                 check_step: 3,
             },
+            switchback_rules: None,
+            growth_mode: GrowthMode::Normal,
+            max_node_degree: None,
+            min_edge_length: 0.0,
+            min_intersection_angle: 0.0,
+            path_priority_sample_count: 0,
         })
     }
 }
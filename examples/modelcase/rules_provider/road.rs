@@ -7,7 +7,7 @@ use street_engine::{
             priority::PathPrioritizationFactors,
             rules::{
                 branch::BranchRules, bridge::BridgeRules, direction::PathDirectionRules,
-                ElevationDiffLimit, TransportRules,
+                ElevationDiffLimit, GrowthMode, TransportRules,
             },
         },
         traits::{PathPrioritizator, TransportRulesProvider},
@@ -36,7 +36,7 @@ impl<'a> TransportRulesProvider for RulesProviderForRoad<'a> {
         let population_density = self.map_provider.get_population_density(site)?;
         let is_street = stage.as_num() > 0;
 
-        let path_normal_length = if metrics.branch_count % 2 == 0 {
+        let path_normal_length = if metrics.branch_count.is_multiple_of(2) {
             0.35
         } else {
             0.45
@@ -49,38 +49,61 @@ impl<'a> TransportRulesProvider for RulesProviderForRoad<'a> {
             // street
             Some(TransportRules {
                 path_normal_length,
+                path_length_jitter: 0.0,
                 path_extra_length_for_intersection: path_normal_length * 0.7,
                 path_slope_elevation_diff_limit,
                 path_grade_separation_elevation_diff_threshold: f64::MAX,
                 branch_rules: BranchRules {
                     branch_density: 0.01 + population_density * 0.99,
                     staging_probability: 0.0,
+                    branch_offset: 0.0,
+                    termination_probability: 0.0,
                 },
+                forbid_branch: false,
                 path_direction_rules: PathDirectionRules {
                     max_radian: std::f64::consts::PI / (5.0 + 1000.0 * population_density),
                     comparison_step: 3,
+                    ..Default::default()
                 },
                 bridge_rules: BridgeRules::default(),
+                switchback_rules: None,
+                growth_mode: GrowthMode::Normal,
+                max_node_degree: None,
+                min_edge_length: 0.0,
+                min_intersection_angle: 0.0,
+                path_priority_sample_count: 0,
             })
         } else {
             // highway
             Some(TransportRules {
                 path_normal_length,
+                path_length_jitter: 0.0,
                 path_extra_length_for_intersection: path_normal_length * 0.7,
                 path_slope_elevation_diff_limit,
                 path_grade_separation_elevation_diff_threshold: f64::MAX,
                 branch_rules: BranchRules {
                     branch_density: 0.2 + population_density * 0.8,
                     staging_probability: 0.97,
+                    branch_offset: 0.0,
+                    termination_probability: 0.0,
                 },
+                forbid_branch: false,
                 path_direction_rules: PathDirectionRules {
                     max_radian: std::f64::consts::PI / (10.0 + 100.0 * population_density),
                     comparison_step: 3,
+                    ..Default::default()
                 },
                 bridge_rules: BridgeRules {
                     max_bridge_length: 25.0,
+                    min_bridge_length: 0.0,
                     check_step: 15,
                 },
+                switchback_rules: None,
+                growth_mode: GrowthMode::Normal,
+                max_node_degree: None,
+                min_edge_length: 0.0,
+                min_intersection_angle: 0.0,
+                path_priority_sample_count: 0,
             })
         }
     }
@@ -99,9 +122,9 @@ impl<'a> PathPrioritizator for RulesProviderForRoad<'a> {
 
         let stage = factor.stage;
         if stage.as_num() > 0 {
-            return Some(path_priority);
+            Some(path_priority)
         } else {
-            return Some(path_priority + 1e5);
+            Some(path_priority + 1e5)
         }
     }
 }
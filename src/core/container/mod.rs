@@ -1,3 +1,3 @@
 mod index_object;
 pub mod path_network;
-mod undirected;
+pub mod undirected;
@@ -1,8 +1,17 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap},
+    sync::OnceLock,
+};
 
 use rstar::RTree;
 
-use crate::core::geometry::{line_segment::LineSegment, site::Site};
+use crate::core::geometry::{
+    angle::Angle,
+    line_segment::LineSegment,
+    polygon::{convex_hull, signed_area},
+    site::Site,
+    triangulation,
+};
 
 use super::{
     index_object::{NodeTreeObject, PathTreeObject},
@@ -13,7 +22,7 @@ pub trait PathNetworkNodeTrait: Into<Site> + Copy + Eq {}
 impl<T> PathNetworkNodeTrait for T where T: Into<Site> + Copy + Eq {}
 
 /// ID for identifying a node in the network.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NodeId(usize);
 
 impl NodeId {
@@ -21,7 +30,7 @@ impl NodeId {
         Self(id)
     }
 
-    fn as_num(&self) -> usize {
+    pub fn as_num(&self) -> usize {
         self.0
     }
 }
@@ -42,6 +51,146 @@ impl NodeIdGenerator {
     }
 }
 
+/// ID for identifying a path (edge) in the network.
+///
+/// Unlike the pair of NodeIds, EdgeId is never reused in the network.
+/// When a path is removed and new paths are added (e.g. a path is split by an intersection),
+/// the new paths are given new EdgeIds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(usize);
+
+impl EdgeId {
+    pub fn new(id: usize) -> Self {
+        Self(id)
+    }
+}
+
+/// EdgeIdGenerator is a simple struct that generates sequential edge ids.
+///
+/// This struct doesn't provide any methods to check the uniqueness of the generated ids.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeIdGenerator {
+    next_id: usize,
+}
+
+impl EdgeIdGenerator {
+    pub fn generate_id(&mut self) -> EdgeId {
+        let id = self.next_id;
+        self.next_id += 1;
+        EdgeId::new(id)
+    }
+}
+
+/// Get the key of the path which does not depend on the order of the nodes.
+fn path_key(start: NodeId, end: NodeId) -> (NodeId, NodeId) {
+    if start < end {
+        (start, end)
+    } else {
+        (end, start)
+    }
+}
+
+/// Clip the line segment from `start` to `end` to the rectangle from the origin to `size`.
+///
+/// Return `None` if the segment is outside the rectangle.
+fn clip_to_rect(
+    start: (f64, f64),
+    end: (f64, f64),
+    size: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (mut t_start, mut t_end) = (0.0_f64, 1.0_f64);
+    for (p, q) in [
+        (-dx, start.0),
+        (dx, size.0 - start.0),
+        (-dy, start.1),
+        (dy, size.1 - start.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t_start = t_start.max(q / p);
+        } else {
+            t_end = t_end.min(q / p);
+        }
+    }
+    if t_start > t_end {
+        return None;
+    }
+    Some((
+        (start.0 + dx * t_start, start.1 + dy * t_start),
+        (start.0 + dx * t_end, start.1 + dy * t_end),
+    ))
+}
+
+/// Entry of the priority queue used for searching distances in the network.
+///
+/// Entries are ordered so that the entry with the shortest distance is popped first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DistanceEntry {
+    node_id: NodeId,
+    distance: f64,
+}
+
+impl Eq for DistanceEntry {}
+
+impl PartialOrd for DistanceEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DistanceEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.node_id.cmp(&self.node_id))
+    }
+}
+
+/// Statistics of turn angles at nodes connected to exactly two paths.
+///
+/// The turn angle is 0 if the two paths form a straight line and π if they fold back.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TurnStats {
+    /// The number of nodes connected to exactly two paths.
+    pub count: usize,
+    /// The mean of turn angles in radian (0 if `count` is 0).
+    pub mean_radian: f64,
+    /// The maximum of turn angles in radian (0 if `count` is 0).
+    pub max_radian: f64,
+}
+
+/// Error of creating a network by `PathNetwork::from`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FromError {
+    /// The path refers to the node index which does not exist.
+    NodeIndexOutOfRange(usize),
+    /// The path is given more than once (in either direction).
+    DuplicateEdge(usize, usize),
+    /// The path connects the node to itself.
+    SelfLoop(usize),
+}
+
+impl std::fmt::Display for FromError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromError::NodeIndexOutOfRange(index) => {
+                write!(f, "node index {} is out of range", index)
+            }
+            FromError::DuplicateEdge(start, end) => {
+                write!(f, "path ({}, {}) is duplicated", start, end)
+            }
+            FromError::SelfLoop(index) => write!(f, "path from node {} to itself", index),
+        }
+    }
+}
+
+impl std::error::Error for FromError {}
+
 /// Path network.
 /// This struct is used to manage nodes and paths between nodes in 2D space.
 ///
@@ -58,6 +207,12 @@ where
     node_tree: RTree<NodeTreeObject<NodeId>>,
     path_connection: UndirectedGraph<NodeId>,
     id_generator: NodeIdGenerator,
+    edge_ids: BTreeMap<(NodeId, NodeId), EdgeId>,
+    edge_id_generator: EdgeIdGenerator,
+    /// Cached labels of the connected components, which are invalidated when paths are changed.
+    component_labels: OnceLock<BTreeMap<NodeId, usize>>,
+    /// The node from which each node was extended (see `ancestry`).
+    parents: BTreeMap<NodeId, NodeId>,
 }
 
 impl<N> Default for PathNetwork<N>
@@ -81,6 +236,10 @@ where
             node_tree: RTree::new(),
             path_connection: UndirectedGraph::new(),
             id_generator: NodeIdGenerator::default(),
+            edge_ids: BTreeMap::new(),
+            edge_id_generator: EdgeIdGenerator::default(),
+            component_labels: OnceLock::new(),
+            parents: BTreeMap::new(),
         }
     }
     /// Get nodes in the network.
@@ -97,6 +256,73 @@ where
             })
     }
 
+    /// Get the paths incident to a node as arms leaving the node.
+    ///
+    /// Each arm is the neighbor and the angle from the node to the neighbor.
+    /// The arms are sorted by the radian of the angle in ascending order (clockwise from the negative y-axis).
+    /// If the node has no paths, an empty vector is returned.
+    pub fn incident_arms(&self, node_id: NodeId) -> Vec<(NodeId, Angle)> {
+        let site: Site = if let Some(node) = self.nodes.get(&node_id) {
+            (*node).into()
+        } else {
+            return Vec::new();
+        };
+        let mut arms = if let Some(neighbors) = self.neighbors_iter(node_id) {
+            neighbors
+                .map(|(neighbor_id, neighbor)| (neighbor_id, site.get_angle(&(*neighbor).into())))
+                .collect::<Vec<_>>()
+        } else {
+            return Vec::new();
+        };
+        arms.sort_by(|(_, a), (_, b)| a.radian().total_cmp(&b.radian()));
+        arms
+    }
+
+    /// Get the arms of the node (see `incident_arms`) whose angles are within `half_width` radian of `center`.
+    ///
+    /// The difference of the angles is measured across the boundary of ±π,
+    /// so the sector around π contains the arms in both sides of the boundary.
+    pub fn arms_in_sector(
+        &self,
+        node_id: NodeId,
+        center: Angle,
+        half_width: f64,
+    ) -> Vec<(NodeId, Angle)> {
+        self.incident_arms(node_id)
+            .into_iter()
+            .filter(|(_, angle)| {
+                Angle::new(angle.radian() - center.radian()).radian().abs() <= half_width
+            })
+            .collect()
+    }
+
+    /// Get the statistics of turn angles at nodes connected to exactly two paths.
+    pub fn turn_angle_stats(&self) -> TurnStats {
+        let turns = self
+            .nodes
+            .keys()
+            .filter_map(|node_id| {
+                let arms = self.incident_arms(*node_id);
+                if arms.len() != 2 {
+                    return None;
+                }
+                let between = Angle::new(arms[1].1.radian() - arms[0].1.radian())
+                    .radian()
+                    .abs();
+                Some(std::f64::consts::PI - between)
+            })
+            .collect::<Vec<_>>();
+
+        if turns.is_empty() {
+            return TurnStats::default();
+        }
+        TurnStats {
+            count: turns.len(),
+            mean_radian: turns.iter().sum::<f64>() / turns.len() as f64,
+            max_radian: turns.iter().fold(0.0, |max, turn| turn.max(max)),
+        }
+    }
+
     fn generate_id_with_check(&mut self) -> NodeId {
         let mut node_id = self.id_generator.generate_id();
         while self.nodes.contains_key(&node_id) {
@@ -105,6 +331,28 @@ where
         node_id
     }
 
+    /// Insert a node to the network and return its NodeId.
+    pub fn insert_node(&mut self, node: N) -> NodeId {
+        self.add_node(node)
+    }
+
+    /// Insert a path between two nodes to the network.
+    ///
+    /// Return `false` if either node does not exist, the nodes are the same, or the path already exists.
+    pub fn insert_path(&mut self, start: NodeId, end: NodeId) -> bool {
+        self.add_path(start, end).is_some()
+    }
+
+    /// Modify all nodes in the network.
+    ///
+    /// `f` must not change the sites of the nodes, since the spatial indices are not updated.
+    pub(crate) fn modify_nodes<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut N),
+    {
+        self.nodes.values_mut().for_each(f);
+    }
+
     /// Add a node to the network.
     pub(crate) fn add_node(&mut self, node: N) -> NodeId {
         let node_id = self.generate_id_with_check();
@@ -114,20 +362,18 @@ where
         node_id
     }
 
-    /// Remove a node from the network.
-    /// This function can be never used, but it is kept for future use.
-    #[allow(dead_code)]
-    fn remove_node(&mut self, node_id: NodeId) -> Option<NodeId> {
-        let neighbors = if let Some(neighbors) = self.path_connection.neighbors_iter(node_id) {
-            neighbors.copied().collect::<Vec<_>>()
+    /// Remove a node and its paths from the network.
+    pub(crate) fn remove_node(&mut self, node_id: NodeId) -> Option<NodeId> {
+        let site = if let Some(node) = self.nodes.get(&node_id) {
+            (*node).into()
         } else {
             return None;
         };
 
-        let site = if let Some(node) = self.nodes.get(&node_id) {
-            (*node).into()
+        let neighbors = if let Some(neighbors) = self.path_connection.neighbors_iter(node_id) {
+            neighbors.copied().collect::<Vec<_>>()
         } else {
-            return None;
+            Vec::new()
         };
 
         neighbors.iter().for_each(|neighbor| {
@@ -137,6 +383,47 @@ where
         self.node_tree.remove(&NodeTreeObject::new(site, node_id));
 
         self.nodes.remove(&node_id);
+        self.parents.remove(&node_id);
+        Some(node_id)
+    }
+
+    /// Replace a node with another node, keeping its NodeId and paths.
+    ///
+    /// The spatial indices of the node and its paths are updated if the site is changed.
+    /// Return `None` if the node does not exist.
+    pub(crate) fn replace_node(&mut self, node_id: NodeId, node: N) -> Option<NodeId> {
+        let old_site: Site = (*self.nodes.get(&node_id)?).into();
+        let new_site: Site = node.into();
+        self.nodes.insert(node_id, node);
+        if old_site == new_site {
+            return Some(node_id);
+        }
+
+        self.node_tree
+            .remove(&NodeTreeObject::new(old_site, node_id));
+        self.node_tree
+            .insert(NodeTreeObject::new(new_site, node_id));
+
+        let neighbors = self
+            .neighbors_iter(node_id)
+            .map(|neighbors| {
+                neighbors
+                    .map(|(neighbor_id, neighbor)| (neighbor_id, (*neighbor).into()))
+                    .collect::<Vec<(NodeId, Site)>>()
+            })
+            .unwrap_or_default();
+        neighbors
+            .into_iter()
+            .for_each(|(neighbor_id, neighbor_site)| {
+                self.path_tree.remove(&PathTreeObject::new(
+                    LineSegment::new(old_site, neighbor_site),
+                    (node_id, neighbor_id),
+                ));
+                self.path_tree.insert(PathTreeObject::new(
+                    LineSegment::new(new_site, neighbor_site),
+                    (node_id, neighbor_id),
+                ));
+            });
         Some(node_id)
     }
 
@@ -158,6 +445,9 @@ where
         };
 
         self.path_connection.add_edge(start, end);
+        self.component_labels.take();
+        self.edge_ids
+            .insert(path_key(start, end), self.edge_id_generator.generate_id());
 
         let (start_site, end_site) = (start_site.into(), end_site.into());
 
@@ -180,6 +470,8 @@ where
         };
 
         self.path_connection.remove_edge(start, end);
+        self.component_labels.take();
+        self.edge_ids.remove(&path_key(start, end));
 
         self.path_tree.remove(&PathTreeObject::new(
             LineSegment::new(start_site.into(), end_site.into()),
@@ -189,11 +481,126 @@ where
         Some((start, end))
     }
 
+    /// Split a path by inserting a new node between two nodes.
+    ///
+    /// The path between `start` and `end` is replaced with the paths `start - node` and `node - end`,
+    /// which are given new EdgeIds.
+    /// Return the NodeId of the inserted node, or `None` if there is no path between the nodes.
+    pub(crate) fn split_path(&mut self, start: NodeId, end: NodeId, node: N) -> Option<NodeId> {
+        if !self.has_path(start, end) {
+            return None;
+        }
+        self.remove_path(start, end)?;
+        let node_id = self.add_node(node);
+        self.add_path(start, node_id);
+        self.add_path(node_id, end);
+        Some(node_id)
+    }
+
+    /// Merge the node `from` into the node `to`.
+    ///
+    /// All paths of `from` are reconnected to `to` and `from` is removed from the network.
+    /// Paths which would become loops or duplicates are dropped.
+    /// Return the NodeId of `to`, or `None` if either node does not exist or they are the same node.
+    pub(crate) fn snap_nodes(&mut self, from: NodeId, to: NodeId) -> Option<NodeId> {
+        if from == to || !self.nodes.contains_key(&to) {
+            return None;
+        }
+        let neighbors = self
+            .neighbors_iter(from)
+            .map(|neighbors| neighbors.map(|(node_id, _)| node_id).collect::<Vec<_>>())
+            .unwrap_or_default();
+        self.remove_node(from)?;
+        neighbors.into_iter().for_each(|neighbor| {
+            self.add_path(to, neighbor);
+        });
+        Some(to)
+    }
+
+    /// Add all nodes and paths of `other` to the network.
+    ///
+    /// Return the new NodeIds of the added nodes, in the order of the NodeIds in `other`.
+    pub(crate) fn append(&mut self, other: &Self) -> Vec<NodeId> {
+        let id_map = other
+            .nodes_iter()
+            .map(|(node_id, node)| (node_id, self.add_node(*node)))
+            .collect::<BTreeMap<_, _>>();
+        other.nodes_iter().for_each(|(node_id, _)| {
+            other
+                .neighbors_iter(node_id)
+                .into_iter()
+                .flatten()
+                .filter(|(neighbor_id, _)| node_id < *neighbor_id)
+                .for_each(|(neighbor_id, _)| {
+                    self.add_path(id_map[&node_id], id_map[&neighbor_id]);
+                });
+        });
+        id_map.into_values().collect()
+    }
+
+    /// Record that the node `child` was extended from the node `parent`.
+    pub(crate) fn set_parent(&mut self, child: NodeId, parent: NodeId) {
+        self.parents.insert(child, parent);
+    }
+
+    /// Get the chain of the nodes from which the node was extended, back to the origin.
+    ///
+    /// The chain starts with `node_id` itself and ends with the node which has no recorded parent
+    /// (e.g. the origin of the growth). Return an empty vector if the node does not exist.
+    pub fn ancestry(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut chain = Vec::new();
+        let mut current = Some(node_id);
+        while let Some(node_id) = current {
+            // the parent may have been removed, and the chain must not loop
+            if !self.nodes.contains_key(&node_id) || chain.contains(&node_id) {
+                break;
+            }
+            chain.push(node_id);
+            current = self.parents.get(&node_id).copied();
+        }
+        chain
+    }
+
+    /// Get the graph of the connections of the paths.
+    pub fn graph(&self) -> &UndirectedGraph<NodeId> {
+        &self.path_connection
+    }
+
     /// Get a node by its NodeId.
     pub fn get_node(&self, node_id: NodeId) -> Option<&N> {
         self.nodes.get(&node_id)
     }
 
+    /// Get the EdgeId of the path between two nodes.
+    ///
+    /// The order of the nodes does not matter.
+    /// Return `None` if there is no path between the nodes.
+    pub fn edge_id(&self, start: NodeId, end: NodeId) -> Option<EdgeId> {
+        self.edge_ids.get(&path_key(start, end)).copied()
+    }
+
+    /// Check if the node exists in the network.
+    ///
+    /// This is useful to validate NodeIds created outside the network (e.g. by `NodeId::new` or deserialization).
+    pub fn contains(&self, node_id: NodeId) -> bool {
+        self.nodes.contains_key(&node_id)
+    }
+
+    /// Get the number of nodes in the network.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the number of paths in the network.
+    pub fn path_count(&self) -> usize {
+        self.path_tree.size()
+    }
+
+    /// Check if the network has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
     /// Check if there is a path between two nodes.
     pub fn has_path(&self, start: NodeId, to: NodeId) -> bool {
         self.path_connection.has_edge(start, to)
@@ -201,13 +608,44 @@ where
 
     /// Search nodes around a site within a radius.
     pub fn nodes_around_site_iter(&self, site: Site, radius: f64) -> impl Iterator<Item = &NodeId> {
+        self.nodes_around_site_iter_with(site, radius, Site::distance)
+    }
+
+    /// Search the nearest node within a radius which satisfies `predicate`.
+    ///
+    /// This can be used to find the nearest node with specific attributes (e.g. the stage).
+    pub fn nearest_node_with<F>(&self, site: Site, radius: f64, predicate: F) -> Option<NodeId>
+    where
+        F: Fn(&N) -> bool,
+    {
+        self.node_tree
+            .nearest_neighbor_iter(&[site.x, site.y])
+            .take_while(|object| object.site().distance(&site) <= radius)
+            .map(|object| *object.node_id())
+            .find(|node_id| self.nodes.get(node_id).is_some_and(&predicate))
+    }
+
+    /// Search nodes around a site within a radius measured by `metric`.
+    ///
+    /// The candidates are pre-filtered by the square of `radius` around the site,
+    /// so `metric` must not be shorter than the Chebyshev distance `max(|dx|, |dy|)`
+    /// (e.g. the euclidean or the Manhattan distance); otherwise some nodes may be missed.
+    pub fn nodes_around_site_iter_with<M>(
+        &self,
+        site: Site,
+        radius: f64,
+        metric: M,
+    ) -> impl Iterator<Item = &NodeId>
+    where
+        M: Fn(&Site, &Site) -> f64,
+    {
         let envelope = rstar::AABB::from_corners(
             [site.x - radius, site.y - radius],
             [site.x + radius, site.y + radius],
         );
         self.node_tree
             .locate_in_envelope(&envelope)
-            .filter(move |object| site.distance(object.site()) <= radius)
+            .filter(move |object| metric(&site, object.site()) <= radius)
             .map(|object| object.node_id())
     }
 
@@ -247,23 +685,175 @@ where
             .map(|object| object.node_ids())
     }
 
+    /// Get the paths crossing the line segment from `start` to `end`, with their crossing sites.
+    ///
+    /// Paths touching the segment (e.g. at their end nodes) are also contained.
+    /// Collinear paths overlapping the segment are not contained (see `overlaps_with_segment`).
+    /// The crossings are sorted by the distance from `start`.
+    pub fn crossings_with_segment(&self, start: Site, end: Site) -> Vec<((NodeId, NodeId), Site)> {
+        let segment = LineSegment::new(start, end);
+        let mut crossings = self
+            .paths_touching_rect_iter(start, end)
+            .filter_map(|(path_start, path_end)| {
+                let path_segment = LineSegment::new(
+                    (*self.nodes.get(path_start)?).into(),
+                    (*self.nodes.get(path_end)?).into(),
+                );
+                let crossing = segment.get_intersection(&path_segment)?;
+                Some(((*path_start, *path_end), crossing))
+            })
+            .collect::<Vec<_>>();
+        crossings.sort_by(|(_, a), (_, b)| a.distance(&start).total_cmp(&b.distance(&start)));
+        crossings
+    }
+
+    /// Get the paths overlapping the line segment from `start` to `end`, with their overlapping portions.
+    ///
+    /// Only collinear paths are contained, which are not detected by `crossings_with_segment`.
+    pub fn overlaps_with_segment(
+        &self,
+        start: Site,
+        end: Site,
+    ) -> Vec<((NodeId, NodeId), LineSegment)> {
+        let segment = LineSegment::new(start, end);
+        self.paths_touching_rect_iter(start, end)
+            .filter_map(|(path_start, path_end)| {
+                let path_segment = LineSegment::new(
+                    (*self.nodes.get(path_start)?).into(),
+                    (*self.nodes.get(path_end)?).into(),
+                );
+                let overlap = segment.overlap(&path_segment)?;
+                Some(((*path_start, *path_end), overlap))
+            })
+            .collect()
+    }
+
+    /// Get the grid of cells touched by any path.
+    ///
+    /// The rectangle `bounds` (a pair of corners) is divided into `cols` x `rows` cells.
+    /// The cells are returned in the row-major order from the corner with the minimum coordinates,
+    /// so that the cell at (`col`, `row`) is at the index `row * cols + col`.
+    /// The cells are traversed along each path, so that all cells which the path passes through are marked.
+    pub fn occupancy_grid(&self, bounds: (Site, Site), cols: usize, rows: usize) -> Vec<bool> {
+        let mut grid = vec![false; cols * rows];
+        let min = Site::new(bounds.0.x.min(bounds.1.x), bounds.0.y.min(bounds.1.y));
+        let max = Site::new(bounds.0.x.max(bounds.1.x), bounds.0.y.max(bounds.1.y));
+        if grid.is_empty() || min.x >= max.x || min.y >= max.y {
+            return grid;
+        }
+
+        // map the sites to the coordinates where each cell is a unit square
+        let to_grid = |site: Site| -> (f64, f64) {
+            (
+                (site.x - min.x) / (max.x - min.x) * cols as f64,
+                (site.y - min.y) / (max.y - min.y) * rows as f64,
+            )
+        };
+        let cell =
+            |v: f64, len: usize| -> isize { (v.floor() as isize).clamp(0, len as isize - 1) };
+
+        self.paths_touching_rect_iter(min, max)
+            .filter_map(|(start, end)| {
+                let start = to_grid((*self.nodes.get(start)?).into());
+                let end = to_grid((*self.nodes.get(end)?).into());
+                clip_to_rect(start, end, (cols as f64, rows as f64))
+            })
+            .for_each(|(start, end)| {
+                let (mut col, mut row) = (cell(start.0, cols), cell(start.1, rows));
+                let (end_col, end_row) = (cell(end.0, cols), cell(end.1, rows));
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let step_col = if dx > 0.0 { 1 } else { -1 };
+                let step_row = if dy > 0.0 { 1 } else { -1 };
+                // the parameters (0 at start, 1 at end) where the path crosses the next cell borders
+                let next_border = |cell: isize, step: isize| (cell + step.max(0)) as f64;
+                let (mut t_col, t_delta_col) = if dx != 0.0 {
+                    ((next_border(col, step_col) - start.0) / dx, 1.0 / dx.abs())
+                } else {
+                    (f64::INFINITY, f64::INFINITY)
+                };
+                let (mut t_row, t_delta_row) = if dy != 0.0 {
+                    ((next_border(row, step_row) - start.1) / dy, 1.0 / dy.abs())
+                } else {
+                    (f64::INFINITY, f64::INFINITY)
+                };
+
+                let cell_count = (end_col - col).abs() + (end_row - row).abs();
+                for _ in 0..=cell_count {
+                    grid[row as usize * cols + col as usize] = true;
+                    if col == end_col && row == end_row {
+                        break;
+                    }
+                    if t_col < t_row && col != end_col || row == end_row {
+                        col += step_col;
+                        t_col += t_delta_col;
+                    } else {
+                        row += step_row;
+                        t_row += t_delta_row;
+                    }
+                }
+            });
+        grid
+    }
+
+    /// Check if any path is within `distance` of the site.
+    fn has_path_within(&self, site: Site, distance: f64) -> bool {
+        self.paths_touching_rect_iter(
+            Site::new(site.x - distance, site.y - distance),
+            Site::new(site.x + distance, site.y + distance),
+        )
+        .filter_map(|(start, end)| {
+            Some(LineSegment::new(
+                (*self.nodes.get(start)?).into(),
+                (*self.nodes.get(end)?).into(),
+            ))
+        })
+        .any(|segment| segment.get_distance(&site) <= distance)
+    }
+
+    /// Get the weighted fraction of the sites within `distance` of any path.
+    ///
+    /// `sample_sites` is the pairs of the sites and their weights (e.g. population).
+    /// The result is in [0, 1], and 0 if the total weight is not positive.
+    pub fn coverage(&self, sample_sites: &[(Site, f64)], distance: f64) -> f64 {
+        let total_weight = sample_sites.iter().map(|(_, weight)| weight).sum::<f64>();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        let covered_weight = sample_sites
+            .iter()
+            .filter(|(site, _)| self.has_path_within(*site, distance))
+            .map(|(_, weight)| weight)
+            .sum::<f64>();
+        covered_weight / total_weight
+    }
+
     /// Parse the network into a list of nodes and paths.
     ///
-    /// This function is not exposed now, but it may be useful in the future.
-    fn parse(&self) -> (Vec<N>, Vec<(usize, usize)>) {
-        let nodes = self.nodes.iter().map(|(_, node)| *node).collect::<Vec<_>>();
+    /// The paths are the pairs of indices of the nodes, which are ordered by their NodeIds.
+    /// This function is not exposed outside the crate now, but it may be useful in the future.
+    pub(crate) fn parse(&self) -> (Vec<N>, Vec<(usize, usize)>) {
+        let indices = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(index, node_id)| (*node_id, index))
+            .collect::<BTreeMap<_, _>>();
+        let nodes = self.nodes.values().copied().collect::<Vec<_>>();
         let paths = self
             .path_tree
             .iter()
             .map(|object| {
                 let (start, end) = object.node_ids();
-                (start.as_num(), end.as_num())
+                (indices[start], indices[end])
             })
             .collect::<Vec<_>>();
         (nodes, paths)
     }
 
-    pub fn from(nodes: Vec<N>, paths: &[(usize, usize)]) -> Option<Self> {
+    /// Create a network from nodes and paths, which are the pairs of indices of `nodes`.
+    ///
+    /// Return an error if any path is invalid.
+    pub fn from(nodes: Vec<N>, paths: &[(usize, usize)]) -> Result<Self, FromError> {
         let mut id_generator = NodeIdGenerator::default();
 
         // distribute NodeIds to nodes
@@ -272,18 +862,25 @@ where
             .map(|node| (id_generator.generate_id(), node))
             .collect::<Vec<_>>();
 
-        // original paths length
-        let paths_len = paths.len();
-
         // convert paths from usize to NodeId
+        let mut path_keys = BTreeSet::new();
         let paths = paths
             .iter()
-            .filter_map(|(start, end)| Some((nodes.get(*start)?.0, nodes.get(*end)?.0)))
-            .collect::<Vec<_>>();
-        // if there are invalid paths, return None
-        if paths.len() != paths_len {
-            return None;
-        }
+            .map(|&(start, end)| {
+                let start_id = nodes
+                    .get(start)
+                    .ok_or(FromError::NodeIndexOutOfRange(start))?
+                    .0;
+                let end_id = nodes.get(end).ok_or(FromError::NodeIndexOutOfRange(end))?.0;
+                if start == end {
+                    return Err(FromError::SelfLoop(start));
+                }
+                if !path_keys.insert(path_key(start_id, end_id)) {
+                    return Err(FromError::DuplicateEdge(start, end));
+                }
+                Ok((start_id, end_id))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let node_into_site = |node: N| -> Site { Into::<Site>::into(node) };
 
@@ -305,6 +902,12 @@ where
 
         let nodes = nodes.into_iter().collect::<BTreeMap<_, _>>();
 
+        let mut edge_id_generator = EdgeIdGenerator::default();
+        let edge_ids = paths
+            .iter()
+            .map(|(start, end)| (path_key(*start, *end), edge_id_generator.generate_id()))
+            .collect::<BTreeMap<_, _>>();
+
         let path_tree = RTree::bulk_load(
             paths
                 .iter()
@@ -320,59 +923,718 @@ where
                 })
                 .collect::<Vec<_>>(),
         );
-        // if there are invalid paths, return None
-        if path_tree.size() != paths_len {
-            return None;
-        }
-        Some(Self {
+        Ok(Self {
             nodes,
             path_tree,
             node_tree,
             path_connection,
             id_generator,
+            edge_ids,
+            edge_id_generator,
+            component_labels: OnceLock::new(),
+            parents: BTreeMap::new(),
         })
     }
 
-    /// Get the optimized path network.
-    pub fn reconstruct(self) -> Option<Self> {
-        let (nodes, paths) = self.parse();
-        Self::from(nodes, &paths)
+    /// Create a network from nodes and paths, which are the pairs of indices of `nodes`.
+    ///
+    /// Return `None` if any path is invalid. See `from` for the details of the errors.
+    pub fn from_opt(nodes: Vec<N>, paths: &[(usize, usize)]) -> Option<Self> {
+        Self::from(nodes, paths).ok()
     }
 
-    /// This function is only for testing
-    #[cfg(test)]
-    fn check_path_state_is_consistent(&self) -> bool {
-        self.path_tree.size() == self.path_connection.size()
-            && self.nodes.len() == self.node_tree.size()
-    }
+    /// Create a network whose nodes are converted by `f`, keeping the NodeIds, paths, and EdgeIds.
+    ///
+    /// This can be used to convert the type of the nodes (e.g. `TransportBuilder::with_node_type`).
+    pub fn map_nodes<M, F>(&self, mut f: F) -> PathNetwork<M>
+    where
+        M: PathNetworkNodeTrait,
+        F: FnMut(NodeId, &N) -> M,
+    {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|(node_id, node)| (*node_id, f(*node_id, node)))
+            .collect::<BTreeMap<_, _>>();
+        let site_of = |node_id: &NodeId| -> Option<Site> { Some((*nodes.get(node_id)?).into()) };
 
-    /// Search the nearest node from a site.
-    /// This function is only for testing, but can be exposed in the future.
-    #[cfg(test)]
-    pub fn search_nearest_node(&self, site: Site) -> Option<NodeId> {
-        self.node_tree
-            .nearest_neighbor(&[site.x, site.y])
-            .map(|object| *object.node_id())
+        let node_tree = RTree::bulk_load(
+            nodes
+                .iter()
+                .map(|(node_id, node)| NodeTreeObject::new((*node).into(), *node_id))
+                .collect::<Vec<_>>(),
+        );
+        let path_tree = RTree::bulk_load(
+            self.path_tree
+                .iter()
+                .filter_map(|object| {
+                    let (start, end) = object.node_ids();
+                    Some(PathTreeObject::new(
+                        LineSegment::new(site_of(start)?, site_of(end)?),
+                        (*start, *end),
+                    ))
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        PathNetwork {
+            nodes,
+            path_tree,
+            node_tree,
+            path_connection: self.path_connection.clone(),
+            id_generator: self.id_generator.clone(),
+            edge_ids: self.edge_ids.clone(),
+            edge_id_generator: self.edge_id_generator.clone(),
+            component_labels: OnceLock::new(),
+            parents: self.parents.clone(),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get the centroid (the average site) of all nodes.
+    ///
+    /// Return `None` if the network has no nodes.
+    pub fn centroid(&self) -> Option<Site> {
+        self.weighted_center(|_, _| 1.0)
+    }
 
-    #[test]
-    fn test_path_network() {
-        let mut network = PathNetwork::new();
-        let node0 = network.add_node(Site::new(0.0, 0.0));
-        let node1 = network.add_node(Site::new(1.0, 1.0));
-        let node2 = network.add_node(Site::new(2.0, 2.0));
-        let node3 = network.add_node(Site::new(3.0, 3.0));
-        let node4 = network.add_node(Site::new(1.0, 4.0));
+    /// Get the weighted average site of all nodes.
+    ///
+    /// The weight of each node is given by `weight` (e.g. the degree or the stage of the node),
+    /// which can be used to find the dense core of the network.
+    /// Return `None` if the total weight is not positive.
+    pub fn weighted_center<F>(&self, weight: F) -> Option<Site>
+    where
+        F: Fn(NodeId, &N) -> f64,
+    {
+        let (sum, total_weight) = self.nodes.iter().fold(
+            (Site::default(), 0.0),
+            |(sum, total_weight), (node_id, node)| {
+                let w = weight(*node_id, node);
+                (sum + Into::<Site>::into(*node) * w, total_weight + w)
+            },
+        );
+        if total_weight <= 0.0 {
+            return None;
+        }
+        Some(sum * (1.0 / total_weight))
+    }
 
-        network.add_path(node0, node1);
-        network.add_path(node1, node2);
-        network.add_path(node2, node3);
-        network.add_path(node3, node4);
+    /// Get the hash of the topology and the geometry of the network.
+    ///
+    /// The hash is calculated from the sites of the nodes and the pairs of the sites of the paths,
+    /// which are quantized by 1e-6 and sorted, so it does not depend on the NodeIds or the order of the insertions.
+    /// The hash is stable across platforms and versions of Rust (FNV-1a), so it can be stored to detect changes of generations.
+    pub fn topology_hash(&self) -> u64 {
+        let quantize = |site: Site| -> (i64, i64) {
+            ((site.x * 1e6).round() as i64, (site.y * 1e6).round() as i64)
+        };
+        let mut sites = self
+            .nodes
+            .values()
+            .map(|node| quantize((*node).into()))
+            .collect::<Vec<_>>();
+        sites.sort();
+        let mut paths = self
+            .path_tree
+            .iter()
+            .filter_map(|object| {
+                let (start, end) = object.node_ids();
+                let start = quantize((*self.nodes.get(start)?).into());
+                let end = quantize((*self.nodes.get(end)?).into());
+                Some((start.min(end), start.max(end)))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let values = std::iter::once(sites.len() as i64)
+            .chain(sites.iter().flat_map(|(x, y)| [*x, *y]))
+            .chain(std::iter::once(paths.len() as i64))
+            .chain(
+                paths
+                    .iter()
+                    .flat_map(|(start, end)| [start.0, start.1, end.0, end.1]),
+            );
+        // FNV-1a
+        values
+            .flat_map(i64::to_le_bytes)
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    /// Get the convex hull of the sites of all nodes.
+    ///
+    /// The hull is returned in the same form as `polygon::convex_hull`.
+    pub fn convex_hull(&self) -> Vec<Site> {
+        let sites = self
+            .nodes
+            .values()
+            .map(|node| (*node).into())
+            .collect::<Vec<Site>>();
+        convex_hull(&sites)
+    }
+
+    /// Get the polygons of the blocks, which are the bounded faces enclosed by the paths.
+    ///
+    /// Each polygon is a ring of sites without repeating the first site at the end,
+    /// ordered from the positive x axis to the positive y axis (see `polygon::signed_area`).
+    /// The unbounded face outside each connected component is excluded,
+    /// and the faces whose area is not greater than `min_area` are filtered out
+    /// (a negative `min_area` is regarded as 0, so that the unbounded faces are always excluded).
+    /// Paths are assumed not to cross each other except at the nodes.
+    pub fn to_block_polygons(&self, min_area: f64) -> Vec<Vec<Site>> {
+        let min_area = min_area.max(0.0);
+        let site_of = |node_id: &NodeId| -> Option<Site> {
+            self.nodes.get(node_id).map(|node| (*node).into())
+        };
+
+        // neighbors of each node, sorted by the angle
+        let neighbors = self
+            .nodes
+            .iter()
+            .map(|(node_id, node)| {
+                let site: Site = (*node).into();
+                let mut neighbors = self
+                    .path_connection
+                    .neighbors_iter(*node_id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|neighbor| Some((*neighbor, site_of(neighbor)? - site)))
+                    .collect::<Vec<_>>();
+                neighbors.sort_by(|(_, a), (_, b)| a.y.atan2(a.x).total_cmp(&b.y.atan2(b.x)));
+                let neighbors = neighbors
+                    .into_iter()
+                    .map(|(neighbor, _)| neighbor)
+                    .collect::<Vec<_>>();
+                (*node_id, neighbors)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut visited = BTreeSet::new();
+        let mut polygons = Vec::new();
+        for (start, start_neighbors) in neighbors.iter() {
+            for end in start_neighbors {
+                if visited.contains(&(*start, *end)) {
+                    continue;
+                }
+                // Trace the face on the left of the directed path,
+                // turning to the next neighbor clockwise at each node.
+                let mut ring = Vec::new();
+                let (mut from, mut to) = (*start, *end);
+                while visited.insert((from, to)) {
+                    ring.extend(site_of(&from));
+                    let Some(to_neighbors) = neighbors.get(&to) else {
+                        break;
+                    };
+                    let index = to_neighbors
+                        .iter()
+                        .position(|node_id| *node_id == from)
+                        .unwrap_or_default();
+                    let next = to_neighbors[(index + to_neighbors.len() - 1) % to_neighbors.len()];
+                    (from, to) = (to, next);
+                }
+                if signed_area(&ring) > min_area {
+                    polygons.push(ring);
+                }
+            }
+        }
+        polygons
+    }
+
+    /// Get the histogram of the orientations of the paths, weighted by their lengths.
+    ///
+    /// The orientation is the angle of the path modulo π (the direction is ignored),
+    /// and the range [0, π) is divided into `bins` bins.
+    /// If `bins` is 0, an empty histogram is returned.
+    pub fn orientation_histogram(&self, bins: usize) -> Vec<f64> {
+        let mut histogram = vec![0.0; bins];
+        if bins == 0 {
+            return histogram;
+        }
+        self.path_tree.iter().for_each(|object| {
+            let (start, end) = object.node_ids();
+            let (start_site, end_site): (Site, Site) =
+                if let (Some(start), Some(end)) = (self.nodes.get(start), self.nodes.get(end)) {
+                    ((*start).into(), (*end).into())
+                } else {
+                    return;
+                };
+            let orientation = start_site
+                .get_angle(&end_site)
+                .radian()
+                .rem_euclid(std::f64::consts::PI);
+            let bin = ((orientation / std::f64::consts::PI * bins as f64) as usize).min(bins - 1);
+            histogram[bin] += start_site.distance(&end_site);
+        });
+        histogram
+    }
+
+    /// Get the connected components of the network.
+    ///
+    /// Each component is a list of NodeIds sorted in ascending order,
+    /// and the components are sorted by their smallest NodeId.
+    /// Nodes without paths are returned as components with a single node.
+    /// This can be used to detect the fragments of the network (e.g. islands without bridges).
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = BTreeSet::new();
+        let mut components = Vec::new();
+        for node_id in self.nodes.keys() {
+            if !visited.insert(*node_id) {
+                continue;
+            }
+            let mut component = vec![*node_id];
+            let mut stack = vec![*node_id];
+            while let Some(current) = stack.pop() {
+                for (neighbor_id, _) in self.neighbors_iter(current).into_iter().flatten() {
+                    if visited.insert(neighbor_id) {
+                        component.push(neighbor_id);
+                        stack.push(neighbor_id);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// Check if there is a route between the two nodes.
+    ///
+    /// The connected components are cached until paths are added or removed,
+    /// so that repeated queries are answered without searching the network.
+    pub fn is_connected(&self, a: NodeId, b: NodeId) -> bool {
+        if !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
+            return false;
+        }
+        if a == b {
+            return true;
+        }
+        let labels = self.component_labels.get_or_init(|| {
+            self.connected_components()
+                .into_iter()
+                .enumerate()
+                .flat_map(|(label, component)| {
+                    component.into_iter().map(move |node_id| (node_id, label))
+                })
+                .collect()
+        });
+        // Nodes added after the cache was created have no paths.
+        matches!((labels.get(&a), labels.get(&b)), (Some(label_a), Some(label_b)) if label_a == label_b)
+    }
+
+    /// Remove the connected components which have less than `min_nodes` nodes.
+    pub fn remove_small_components(&mut self, min_nodes: usize) {
+        self.connected_components()
+            .into_iter()
+            .filter(|component| component.len() < min_nodes)
+            .flatten()
+            .for_each(|node_id| {
+                self.remove_node(node_id);
+            });
+    }
+
+    /// Get the edges of the Delaunay triangulation of the nodes.
+    ///
+    /// The edges are not related to the paths in the network.
+    /// Each edge is returned once (the smaller NodeId first).
+    /// Nodes at the same site are treated as one node (only the node with the smallest NodeId is used).
+    pub fn delaunay_edges(&self) -> Vec<(NodeId, NodeId)> {
+        let (node_ids, sites): (Vec<_>, Vec<_>) = self
+            .nodes
+            .iter()
+            .map(|(node_id, node)| (*node_id, Into::<Site>::into(*node)))
+            .unzip();
+        triangulation::delaunay_edges(&sites)
+            .into_iter()
+            .map(|(a, b)| (node_ids[a], node_ids[b]))
+            .collect()
+    }
+
+    /// Create a network of the euclidean minimum spanning tree of nodes.
+    ///
+    /// The tree is calculated by Prim's algorithm on the complete graph of the nodes, which takes O(n^2) time.
+    /// This is useful to create the skeleton of the network (e.g. trunk roads between cities).
+    pub fn euclidean_mst(nodes: &[N]) -> Self {
+        let sites = nodes
+            .iter()
+            .map(|node| Into::<Site>::into(*node))
+            .collect::<Vec<_>>();
+
+        let mut in_tree = vec![false; sites.len()];
+        // the shortest distance to the tree and the nearest node in the tree
+        let mut nearest: Vec<(f64, Option<usize>)> = vec![(f64::INFINITY, None); sites.len()];
+        let mut paths = Vec::with_capacity(sites.len().saturating_sub(1));
+
+        if !sites.is_empty() {
+            nearest[0].0 = 0.0;
+        }
+        for _ in 0..sites.len() {
+            let next = (0..sites.len())
+                .filter(|i| !in_tree[*i])
+                .min_by(|a, b| nearest[*a].0.total_cmp(&nearest[*b].0));
+            let next = if let Some(next) = next {
+                next
+            } else {
+                break;
+            };
+            in_tree[next] = true;
+            if let Some(parent) = nearest[next].1 {
+                paths.push((parent, next));
+            }
+            for i in 0..sites.len() {
+                if in_tree[i] {
+                    continue;
+                }
+                let distance = sites[next].distance(&sites[i]);
+                if distance < nearest[i].0 {
+                    nearest[i] = (distance, Some(next));
+                }
+            }
+        }
+
+        Self::from(nodes.to_vec(), &paths).unwrap_or_default()
+    }
+
+    /// Get the optimized path network.
+    ///
+    /// The network is rebuilt from its nodes and paths, so that the spatial indices are bulk-loaded
+    /// and the NodeIds (and EdgeIds) are renumbered from 0 without gaps, keeping the order of the NodeIds.
+    /// Note that NodeIds obtained from the original network may refer to other nodes in the optimized network.
+    ///
+    /// The recorded parents of the nodes (see `ancestry`) are kept with the renumbered NodeIds.
+    ///
+    /// This is the optimization used by `TransportBuilder::snapshot`.
+    pub fn reconstruct(self) -> Option<Self> {
+        let (nodes, paths) = self.parse();
+        let mut network = Self::from_opt(nodes, &paths)?;
+        // NodeIds are distributed in the order of the original NodeIds
+        let new_ids = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(index, node_id)| (*node_id, NodeId::new(index)))
+            .collect::<BTreeMap<_, _>>();
+        network.parents = self
+            .parents
+            .iter()
+            .filter_map(|(child, parent)| Some((*new_ids.get(child)?, *new_ids.get(parent)?)))
+            .collect();
+        Some(network)
+    }
+
+    /// Get the nodes reachable from `start` within the travel distance `budget`.
+    ///
+    /// The travel distance is the sum of the euclidean lengths of the paths.
+    /// Each reachable node is returned with its shortest travel distance from `start`, including `start` itself.
+    /// If `start` is not in the network, an empty vector is returned.
+    pub fn reachable_within(&self, start: NodeId, budget: f64) -> Vec<(NodeId, f64)> {
+        self.shortest_distances(start, budget).into_iter().collect()
+    }
+
+    /// Search the shortest travel distances from `start` to the nodes within `budget` (Dijkstra's algorithm).
+    fn shortest_distances(&self, start: NodeId, budget: f64) -> BTreeMap<NodeId, f64> {
+        let mut distances = BTreeMap::new();
+        if !self.nodes.contains_key(&start) || budget < 0.0 {
+            return distances;
+        }
+
+        let mut heap = BinaryHeap::new();
+        heap.push(DistanceEntry {
+            node_id: start,
+            distance: 0.0,
+        });
+
+        while let Some(DistanceEntry { node_id, distance }) = heap.pop() {
+            if distances.contains_key(&node_id) {
+                continue;
+            }
+            distances.insert(node_id, distance);
+
+            let site: Site = if let Some(node) = self.nodes.get(&node_id) {
+                (*node).into()
+            } else {
+                continue;
+            };
+            let neighbors = if let Some(neighbors) = self.neighbors_iter(node_id) {
+                neighbors
+            } else {
+                continue;
+            };
+            for (neighbor_id, neighbor) in neighbors {
+                if distances.contains_key(&neighbor_id) {
+                    continue;
+                }
+                let neighbor_site: Site = (*neighbor).into();
+                let neighbor_distance = distance + site.distance(&neighbor_site);
+                if neighbor_distance <= budget {
+                    heap.push(DistanceEntry {
+                        node_id: neighbor_id,
+                        distance: neighbor_distance,
+                    });
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Get the shortest path from `start` to `goal`, where the cost of each path is given by `weight`.
+    ///
+    /// `weight` is called with the nodes of both ends of the path (in the direction of the travel),
+    /// and must return a non-negative cost (e.g. the length divided by the speed of the stage of the path).
+    /// Return the NodeIds on the path from `start` to `goal` and the total cost,
+    /// or `None` if either node does not exist or they are not connected.
+    pub fn shortest_path_weighted<F>(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        weight: F,
+    ) -> Option<(Vec<NodeId>, f64)>
+    where
+        F: Fn(&N, &N) -> f64,
+    {
+        if !self.nodes.contains_key(&start) || !self.nodes.contains_key(&goal) {
+            return None;
+        }
+
+        let mut costs = BTreeMap::new();
+        let mut tentative_costs = BTreeMap::new();
+        let mut previous = BTreeMap::new();
+        let mut heap = BinaryHeap::new();
+        heap.push(DistanceEntry {
+            node_id: start,
+            distance: 0.0,
+        });
+
+        while let Some(DistanceEntry { node_id, distance }) = heap.pop() {
+            if costs.contains_key(&node_id) {
+                continue;
+            }
+            costs.insert(node_id, distance);
+            if node_id == goal {
+                break;
+            }
+
+            let node = if let Some(node) = self.nodes.get(&node_id) {
+                node
+            } else {
+                continue;
+            };
+            let neighbors = if let Some(neighbors) = self.neighbors_iter(node_id) {
+                neighbors
+            } else {
+                continue;
+            };
+            for (neighbor_id, neighbor) in neighbors {
+                if costs.contains_key(&neighbor_id) {
+                    continue;
+                }
+                let neighbor_cost = distance + weight(node, neighbor);
+                if tentative_costs
+                    .get(&neighbor_id)
+                    .is_none_or(|cost| neighbor_cost < *cost)
+                {
+                    tentative_costs.insert(neighbor_id, neighbor_cost);
+                    previous.insert(neighbor_id, node_id);
+                    heap.push(DistanceEntry {
+                        node_id: neighbor_id,
+                        distance: neighbor_cost,
+                    });
+                }
+            }
+        }
+
+        let cost = *costs.get(&goal)?;
+        let mut path = vec![goal];
+        // `start` has no previous node
+        while let Some(node_id) = previous.get(path.last()?) {
+            path.push(*node_id);
+        }
+        path.reverse();
+        Some((path, cost))
+    }
+
+    /// Get the shortest path from `from` to `to`, where the cost of each path is its euclidean length.
+    ///
+    /// Return the NodeIds on the path from `from` to `to` and the total length,
+    /// or `None` if either node does not exist or they are not connected.
+    pub fn shortest_path(&self, from: NodeId, to: NodeId) -> Option<(Vec<NodeId>, f64)> {
+        self.shortest_path_weighted(from, to, |a, b| (*a).into().distance(&(*b).into()))
+    }
+
+    /// Get the betweenness centrality of each node, where the length of each path is the euclidean distance.
+    ///
+    /// The betweenness of a node is the sum of the fractions of the shortest paths between all pairs of other nodes
+    /// which pass through the node (Brandes' algorithm). Each unordered pair is counted once.
+    ///
+    /// If `sample_nodes` is given, only the shortest paths from the sampled nodes are searched,
+    /// and the values are scaled by the ratio of the number of all nodes to the number of the sampled nodes.
+    /// This can be used to estimate the betweenness of large networks.
+    /// The sampled nodes which do not exist in the network are ignored.
+    pub fn betweenness(&self, sample_nodes: Option<&[NodeId]>) -> HashMap<NodeId, f64> {
+        let mut betweenness = self
+            .nodes
+            .keys()
+            .map(|node_id| (*node_id, 0.0))
+            .collect::<HashMap<_, _>>();
+
+        let sources = if let Some(sample_nodes) = sample_nodes {
+            sample_nodes
+                .iter()
+                .copied()
+                .filter(|node_id| self.nodes.contains_key(node_id))
+                .collect::<BTreeSet<_>>()
+        } else {
+            self.nodes.keys().copied().collect::<BTreeSet<_>>()
+        };
+        if sources.is_empty() {
+            return betweenness;
+        }
+
+        for source in &sources {
+            self.accumulate_betweenness(*source, &mut betweenness);
+        }
+
+        // each pair is counted from both ends
+        let scale = self.nodes.len() as f64 / sources.len() as f64 * 0.5;
+        betweenness.values_mut().for_each(|value| *value *= scale);
+        betweenness
+    }
+
+    /// Add the dependencies of `source` on the other nodes to `betweenness` (a step of Brandes' algorithm).
+    fn accumulate_betweenness(&self, source: NodeId, betweenness: &mut HashMap<NodeId, f64>) {
+        // tolerance to treat the lengths of the paths as equal
+        const EPSILON: f64 = 1e-9;
+
+        let mut settled = BTreeSet::new();
+        // nodes in order of the distance from the source
+        let mut order = Vec::new();
+        let mut tentative_costs = BTreeMap::new();
+        let mut path_counts = BTreeMap::new();
+        let mut predecessors: BTreeMap<NodeId, Vec<NodeId>> = BTreeMap::new();
+        let mut heap = BinaryHeap::new();
+        tentative_costs.insert(source, 0.0);
+        path_counts.insert(source, 1.0);
+        heap.push(DistanceEntry {
+            node_id: source,
+            distance: 0.0,
+        });
+
+        while let Some(DistanceEntry { node_id, distance }) = heap.pop() {
+            if !settled.insert(node_id) {
+                continue;
+            }
+            order.push(node_id);
+
+            let site: Site = if let Some(node) = self.nodes.get(&node_id) {
+                (*node).into()
+            } else {
+                continue;
+            };
+            let neighbors = if let Some(neighbors) = self.neighbors_iter(node_id) {
+                neighbors
+            } else {
+                continue;
+            };
+            let path_count = path_counts.get(&node_id).copied().unwrap_or(0.0);
+            for (neighbor_id, neighbor) in neighbors {
+                if settled.contains(&neighbor_id) {
+                    continue;
+                }
+                let neighbor_cost = distance + site.distance(&(*neighbor).into());
+                match tentative_costs.get(&neighbor_id) {
+                    Some(cost) if (neighbor_cost - cost).abs() <= EPSILON => {
+                        *path_counts.entry(neighbor_id).or_insert(0.0) += path_count;
+                        predecessors.entry(neighbor_id).or_default().push(node_id);
+                    }
+                    Some(cost) if neighbor_cost > *cost => {}
+                    _ => {
+                        tentative_costs.insert(neighbor_id, neighbor_cost);
+                        path_counts.insert(neighbor_id, path_count);
+                        predecessors.insert(neighbor_id, vec![node_id]);
+                        heap.push(DistanceEntry {
+                            node_id: neighbor_id,
+                            distance: neighbor_cost,
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut dependencies: BTreeMap<NodeId, f64> = BTreeMap::new();
+        for node_id in order.into_iter().rev() {
+            let dependency = dependencies.get(&node_id).copied().unwrap_or(0.0);
+            let path_count = path_counts.get(&node_id).copied().unwrap_or(0.0);
+            for predecessor in predecessors.get(&node_id).into_iter().flatten() {
+                let predecessor_count = path_counts.get(predecessor).copied().unwrap_or(0.0);
+                *dependencies.entry(*predecessor).or_insert(0.0) +=
+                    predecessor_count / path_count * (1.0 + dependency);
+            }
+            if node_id != source {
+                *betweenness.entry(node_id).or_insert(0.0) += dependency;
+            }
+        }
+    }
+
+    /// Get the isochrone polygons from `start` for each travel distance in `budgets`.
+    ///
+    /// Each polygon is the convex hull of the nodes reachable within the budget (see `reachable_within`),
+    /// returned as a ring of sites without repeating the first site at the end.
+    /// The convex hull overestimates the reachable area for concave networks, but it is cheap and always yields a simple polygon.
+    ///
+    /// If fewer than 3 non-collinear nodes are reachable, the returned ring is degenerated (less than 3 sites).
+    pub fn isochrone(&self, start: NodeId, budgets: &[f64]) -> Vec<Vec<Site>> {
+        let max_budget = budgets.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let distances = self.shortest_distances(start, max_budget);
+
+        budgets
+            .iter()
+            .map(|budget| {
+                let sites = distances
+                    .iter()
+                    .filter(|(_, distance)| **distance <= *budget)
+                    .filter_map(|(node_id, _)| Some((*self.nodes.get(node_id)?).into()))
+                    .collect::<Vec<Site>>();
+                convex_hull(&sites)
+            })
+            .collect()
+    }
+
+    /// This function is only for testing
+    #[cfg(test)]
+    fn check_path_state_is_consistent(&self) -> bool {
+        self.path_tree.size() == self.path_connection.edge_count()
+            && self.nodes.len() == self.node_tree.size()
+    }
+
+    /// Search the nearest node from a site.
+    /// This function is only for testing, but can be exposed in the future.
+    #[cfg(test)]
+    pub fn search_nearest_node(&self, site: Site) -> Option<NodeId> {
+        self.node_tree
+            .nearest_neighbor(&[site.x, site.y])
+            .map(|object| *object.node_id())
+    }
+}
+
+#[cfg(test)]
+#[allow(unknown_lints, clippy::useless_vec, clippy::manual_is_multiple_of)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_network() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 1.0));
+        let node2 = network.add_node(Site::new(2.0, 2.0));
+        let node3 = network.add_node(Site::new(3.0, 3.0));
+        let node4 = network.add_node(Site::new(1.0, 4.0));
+
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+        network.add_path(node2, node3);
+        network.add_path(node3, node4);
         network.add_path(node4, node2);
 
         assert!(network.has_path(node0, node1));
@@ -395,6 +1657,22 @@ mod tests {
         assert!(network.check_path_state_is_consistent());
     }
 
+    #[test]
+    fn test_contains() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 1.0));
+        network.add_path(node0, node1);
+
+        assert!(network.contains(node0));
+        assert!(network.contains(node1));
+        assert!(!network.contains(NodeId::new(100)));
+
+        network.remove_node(node1);
+        assert!(!network.contains(node1));
+        assert!(network.contains(node0));
+    }
+
     #[test]
     fn test_path_crossing_no_crosses() {
         let mut network = PathNetwork::new();
@@ -565,6 +1843,940 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shortest_path() {
+        // the network of the first loop of `test_complex_network`
+        let sites = (0..100)
+            .map(|i| Site::new(xorshift(i * 2) as f64, xorshift(i * 2 + 1) as f64))
+            .collect::<Vec<_>>();
+        let mut network = PathNetwork::new();
+        let nodeids = sites
+            .iter()
+            .map(|site| network.add_node(*site))
+            .collect::<Vec<_>>();
+        (0..sites.len()).for_each(|i| {
+            (0..sites.len()).for_each(|j| {
+                let id = i * sites.len() + j;
+                if xorshift(id).is_multiple_of(2) {
+                    network.add_path(nodeids[i], nodeids[j]);
+                }
+            });
+        });
+        let isolated = network.add_node(Site::new(-1.0, -1.0));
+
+        let distances = network
+            .reachable_within(nodeids[0], f64::MAX)
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        for goal in nodeids.iter().skip(1).step_by(7) {
+            let (path, length) = network.shortest_path(nodeids[0], *goal).unwrap();
+            assert_eq!(path.first(), Some(&nodeids[0]));
+            assert_eq!(path.last(), Some(goal));
+            let path_length = path
+                .windows(2)
+                .map(|pair| {
+                    assert!(network.has_path(pair[0], pair[1]));
+                    sites[pair[0].as_num()].distance(&sites[pair[1].as_num()])
+                })
+                .sum::<f64>();
+            assert!((path_length - length).abs() <= length * 1e-9);
+            assert!((distances[goal] - length).abs() <= length * 1e-9);
+        }
+
+        assert_eq!(
+            network.shortest_path(nodeids[0], nodeids[0]),
+            Some((vec![nodeids[0]], 0.0))
+        );
+        assert_eq!(network.shortest_path(nodeids[0], isolated), None);
+        assert_eq!(network.shortest_path(NodeId::new(1000), nodeids[0]), None);
+    }
+
+    #[test]
+    fn test_split_and_snap() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(2.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 2.0));
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+
+        let middle = network
+            .split_path(node0, node1, Site::new(1.0, 0.0))
+            .unwrap();
+        assert!(!network.has_path(node0, node1));
+        assert!(network.has_path(node0, middle));
+        assert!(network.has_path(middle, node1));
+        assert!(network
+            .split_path(node0, node2, Site::new(1.0, 1.0))
+            .is_none());
+        assert!(network.check_path_state_is_consistent());
+
+        assert_eq!(network.snap_nodes(node1, middle), Some(middle));
+        assert!(!network.contains(node1));
+        assert!(network.has_path(node0, middle));
+        assert!(network.has_path(middle, node2));
+        assert!(network.check_path_state_is_consistent());
+
+        // the path between merged nodes is dropped
+        assert_eq!(network.snap_nodes(middle, node0), Some(node0));
+        assert!(network.has_path(node0, node2));
+        assert!(!network.has_path(node0, node0));
+        assert!(network.check_path_state_is_consistent());
+    }
+
+    #[test]
+    fn test_euclidean_mst() {
+        let sites = vec![
+            Site::new(0.0, 0.0),
+            Site::new(10.0, 0.0),
+            Site::new(1.0, 0.0),
+            Site::new(1.0, 2.0),
+            Site::new(9.0, 1.0),
+        ];
+        let network = PathNetwork::euclidean_mst(&sites);
+        let node_ids = sites
+            .iter()
+            .map(|site| network.search_nearest_node(*site).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(network.check_path_state_is_consistent());
+        assert_eq!(network.path_connection.edge_count(), 4);
+        assert!(network.has_path(node_ids[0], node_ids[2]));
+        assert!(network.has_path(node_ids[2], node_ids[3]));
+        assert!(network.has_path(node_ids[1], node_ids[4]));
+        assert!(network.has_path(node_ids[2], node_ids[4]));
+
+        assert_eq!(
+            PathNetwork::<Site>::euclidean_mst(&[]).nodes_iter().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_delaunay_edges() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(4.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 1.0));
+        let node3 = network.add_node(Site::new(2.0, -1.0));
+        network.add_path(node0, node1);
+
+        assert_eq!(
+            network.delaunay_edges(),
+            vec![
+                (node0, node2),
+                (node0, node3),
+                (node1, node2),
+                (node1, node3),
+                (node2, node3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incident_arms() {
+        let mut network = PathNetwork::new();
+        let center = network.add_node(Site::new(0.0, 0.0));
+        let east = network.add_node(Site::new(1.0, 0.0));
+        let north = network.add_node(Site::new(0.0, -1.0));
+        let west = network.add_node(Site::new(-1.0, 0.0));
+        let south = network.add_node(Site::new(0.0, 1.0));
+        let isolated = network.add_node(Site::new(5.0, 5.0));
+
+        network.add_path(center, south);
+        network.add_path(center, east);
+        network.add_path(center, west);
+        network.add_path(center, north);
+
+        let arms = network.incident_arms(center);
+        assert_eq!(
+            arms.iter().map(|(node_id, _)| *node_id).collect::<Vec<_>>(),
+            vec![west, north, east, south]
+        );
+        assert_eq!(arms[1].1, Angle::new(0.0));
+        assert_eq!(arms[2].1, Angle::new(std::f64::consts::PI * 0.5));
+
+        assert_eq!(
+            network.incident_arms(east),
+            vec![(center, Angle::new(-std::f64::consts::PI * 0.5))]
+        );
+        assert!(network.incident_arms(isolated).is_empty());
+    }
+
+    #[test]
+    fn test_arms_in_sector() {
+        let mut network = PathNetwork::new();
+        let center = network.add_node(Site::new(0.0, 0.0));
+        let [north, east, south_east, south_west] =
+            [(0.0, -1.0), (1.0, 0.0), (0.2, 1.0), (-0.2, 1.0)]
+                .map(|(x, y)| network.add_node(Site::new(x, y)));
+        for node_id in [north, east, south_east, south_west] {
+            network.add_path(center, node_id);
+        }
+        let arm_ids = |arms: Vec<(NodeId, Angle)>| {
+            arms.into_iter()
+                .map(|(node_id, _)| node_id)
+                .collect::<Vec<_>>()
+        };
+        let quarter = std::f64::consts::PI * 0.25;
+
+        assert_eq!(
+            arm_ids(network.arms_in_sector(center, Angle::new(0.0), quarter)),
+            vec![north]
+        );
+        assert_eq!(
+            arm_ids(network.arms_in_sector(center, Angle::new(quarter), quarter)),
+            vec![north, east]
+        );
+        // across the boundary of ±π
+        assert_eq!(
+            arm_ids(network.arms_in_sector(center, Angle::new(std::f64::consts::PI), 0.3)),
+            vec![south_west, south_east]
+        );
+        assert!(network
+            .arms_in_sector(NodeId::new(100), Angle::new(0.0), quarter)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_turn_angle_stats() {
+        let mut network = PathNetwork::new();
+        assert_eq!(network.turn_angle_stats(), TurnStats::default());
+
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 0.0));
+        let node3 = network.add_node(Site::new(2.0, 1.0));
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+        network.add_path(node2, node3);
+
+        let stats = network.turn_angle_stats();
+        assert_eq!(stats.count, 2);
+        assert!((stats.mean_radian - std::f64::consts::PI * 0.25).abs() < 1e-9);
+        assert!((stats.max_radian - std::f64::consts::PI * 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orientation_histogram() {
+        let mut network = PathNetwork::new();
+        let center = network.add_node(Site::new(0.0, 0.0));
+        let north = network.add_node(Site::new(0.0, -2.0));
+        let south = network.add_node(Site::new(0.0, 1.0));
+        let east = network.add_node(Site::new(3.0, 0.0));
+        network.add_path(center, north);
+        network.add_path(south, center);
+        network.add_path(center, east);
+
+        // north-south paths are in the first bin, and east-west paths are in the third bin
+        assert_eq!(network.orientation_histogram(4), vec![3.0, 0.0, 3.0, 0.0]);
+        assert!(network.orientation_histogram(0).is_empty());
+    }
+
+    #[test]
+    fn test_map_nodes() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct DistrictNode {
+            site: Site,
+            district: usize,
+        }
+
+        impl From<DistrictNode> for Site {
+            fn from(node: DistrictNode) -> Self {
+                node.site
+            }
+        }
+
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(5.0, 0.0));
+        network.add_path(node0, node1);
+
+        let mapped = network.map_nodes(|_, site| DistrictNode {
+            site: *site,
+            district: if site.x < 2.5 { 0 } else { 1 },
+        });
+        assert_eq!(mapped.get_node(node1).unwrap().district, 1);
+        assert!(mapped.has_path(node0, node1));
+        assert_eq!(mapped.edge_id(node0, node1), network.edge_id(node0, node1));
+        assert!(mapped.check_path_state_is_consistent());
+    }
+
+    #[test]
+    fn test_crossings_with_segment() {
+        let mut network = PathNetwork::new();
+        let nodes = [
+            (1.0, -1.0),
+            (1.0, 1.0),
+            (3.0, -1.0),
+            (3.0, 1.0),
+            (5.0, -1.0),
+            (5.0, 1.0),
+        ]
+        .iter()
+        .map(|(x, y)| network.add_node(Site::new(*x, *y)))
+        .collect::<Vec<_>>();
+        network.add_path(nodes[2], nodes[3]);
+        network.add_path(nodes[0], nodes[1]);
+        network.add_path(nodes[4], nodes[5]);
+
+        let crossings = network.crossings_with_segment(Site::new(0.0, 0.0), Site::new(4.0, 0.0));
+        assert_eq!(crossings.len(), 2);
+        assert_eq!(crossings[0].1, Site::new(1.0, 0.0));
+        assert_eq!(crossings[1].1, Site::new(3.0, 0.0));
+        assert!(crossings[0].0 == (nodes[0], nodes[1]) || crossings[0].0 == (nodes[1], nodes[0]));
+
+        assert!(network
+            .crossings_with_segment(Site::new(0.0, 2.0), Site::new(4.0, 2.0))
+            .is_empty());
+
+        // the path laid on the existing path
+        let overlaps = network.overlaps_with_segment(Site::new(3.0, 0.0), Site::new(3.0, 3.0));
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(
+            overlaps[0].1,
+            LineSegment::new(Site::new(3.0, 0.0), Site::new(3.0, 1.0))
+        );
+        assert!(network
+            .overlaps_with_segment(Site::new(0.0, 0.0), Site::new(4.0, 0.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut network = PathNetwork::new();
+        let node0 = network.insert_node(Site::new(0.0, 0.0));
+        let node1 = network.insert_node(Site::new(1.0, 0.0));
+
+        assert!(network.insert_path(node0, node1));
+        assert!(!network.insert_path(node1, node0));
+        assert!(!network.insert_path(node0, node0));
+        assert!(!network.insert_path(node0, NodeId::new(100)));
+        assert!(network.has_path(node0, node1));
+        assert!(network.check_path_state_is_consistent());
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut network = PathNetwork::new();
+        assert!(network.is_empty());
+        assert_eq!((network.node_count(), network.path_count()), (0, 0));
+
+        let nodes = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .map(|(x, y)| network.add_node(Site::new(x, y)));
+        for i in 0..4 {
+            network.add_path(nodes[i], nodes[(i + 1) % 4]);
+        }
+        assert!(!network.is_empty());
+        assert_eq!((network.node_count(), network.path_count()), (4, 4));
+
+        network.remove_path(nodes[0], nodes[1]);
+        assert_eq!((network.node_count(), network.path_count()), (4, 3));
+        network.remove_node(nodes[2]);
+        assert_eq!((network.node_count(), network.path_count()), (3, 1));
+    }
+
+    #[test]
+    fn test_remove_path_with_swapped_nodes() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 2.0));
+        let node2 = network.add_node(Site::new(-1.0, 3.0));
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+
+        assert_eq!(network.remove_path(node1, node0), Some((node1, node0)));
+        assert!(!network.has_path(node0, node1));
+        assert!(network.has_path(node1, node2));
+        assert!(network.edge_id(node0, node1).is_none());
+        assert!(network
+            .paths_touching_rect_iter(Site::new(0.0, 0.0), Site::new(1.0, 2.0))
+            .all(|path| *path == (node1, node2)));
+        assert!(network.check_path_state_is_consistent());
+    }
+
+    #[test]
+    fn test_reconstruct() {
+        let mut network = PathNetwork::new();
+        let sites = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        let nodes = sites
+            .iter()
+            .map(|(x, y)| network.add_node(Site::new(*x, *y)))
+            .collect::<Vec<_>>();
+        network.add_path(nodes[0], nodes[1]);
+        network.add_path(nodes[2], nodes[3]);
+        network.add_path(nodes[0], nodes[3]);
+        network.remove_node(nodes[1]);
+
+        let reconstructed = network.clone().reconstruct().unwrap();
+        assert!(reconstructed.check_path_state_is_consistent());
+        // NodeIds are renumbered without gaps
+        assert_eq!(
+            reconstructed
+                .nodes_iter()
+                .map(|(node_id, node)| (node_id.as_num(), *node))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, Site::new(0.0, 0.0)),
+                (1, Site::new(2.0, 0.0)),
+                (2, Site::new(3.0, 0.0))
+            ]
+        );
+        assert!(reconstructed.has_path(NodeId::new(1), NodeId::new(2)));
+        assert!(reconstructed.has_path(NodeId::new(0), NodeId::new(2)));
+        assert!(!reconstructed.has_path(NodeId::new(0), NodeId::new(1)));
+    }
+
+    #[test]
+    fn test_is_connected() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 0.0));
+        network.add_path(node0, node1);
+
+        assert!(network.is_connected(node0, node1));
+        assert!(!network.is_connected(node0, node2));
+        assert!(network.is_connected(node2, node2));
+
+        // the cache is invalidated by changes of paths
+        network.add_path(node1, node2);
+        assert!(network.is_connected(node0, node2));
+        let node3 = network.add_node(Site::new(3.0, 0.0));
+        assert!(!network.is_connected(node2, node3));
+        network.remove_path(node0, node1);
+        assert!(!network.is_connected(node0, node2));
+        network.remove_node(node3);
+        assert!(!network.is_connected(node3, node3));
+    }
+
+    #[test]
+    fn test_connected_components() {
+        // a ring of 6 nodes, which is split into 2 fragments by removing 2 paths
+        let mut network = PathNetwork::new();
+        let nodes = (0..6)
+            .map(|i| {
+                let angle = Angle::new(i as f64 * std::f64::consts::PI / 3.0);
+                network.add_node(Site::new(angle.unit_x(), angle.unit_y()))
+            })
+            .collect::<Vec<_>>();
+        for i in 0..6 {
+            network.add_path(nodes[i], nodes[(i + 1) % 6]);
+        }
+        let isolated = network.add_node(Site::new(5.0, 5.0));
+        assert_eq!(
+            network
+                .connected_components()
+                .iter()
+                .map(|component| component.len())
+                .collect::<Vec<_>>(),
+            vec![6, 1]
+        );
+
+        network.remove_path(nodes[1], nodes[2]);
+        network.remove_path(nodes[4], nodes[5]);
+        let components = network.connected_components();
+        assert_eq!(components.len(), 3);
+        assert_eq!(
+            components,
+            vec![
+                vec![nodes[0], nodes[1], nodes[5]],
+                vec![nodes[2], nodes[3], nodes[4]],
+                vec![isolated]
+            ]
+        );
+
+        assert!(PathNetwork::<Site>::new().connected_components().is_empty());
+    }
+
+    #[test]
+    fn test_remove_small_components() {
+        let mut network = PathNetwork::new();
+        let sites = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (10.0, 0.0), (11.0, 0.0)];
+        let nodes = sites
+            .iter()
+            .map(|(x, y)| network.add_node(Site::new(*x, *y)))
+            .collect::<Vec<_>>();
+        let isolated = network.add_node(Site::new(20.0, 0.0));
+        network.add_path(nodes[0], nodes[1]);
+        network.add_path(nodes[1], nodes[2]);
+        network.add_path(nodes[3], nodes[4]);
+
+        assert_eq!(
+            network.connected_components(),
+            vec![
+                vec![nodes[0], nodes[1], nodes[2]],
+                vec![nodes[3], nodes[4]],
+                vec![isolated]
+            ]
+        );
+
+        network.remove_small_components(3);
+        assert_eq!(
+            network.connected_components(),
+            vec![vec![nodes[0], nodes[1], nodes[2]]]
+        );
+        assert!(network.check_path_state_is_consistent());
+        assert_eq!(
+            network.search_nearest_node(Site::new(10.0, 0.0)),
+            Some(nodes[2])
+        );
+    }
+
+    #[test]
+    fn test_nodes_around_site_iter_with() {
+        let mut network = PathNetwork::new();
+        let east = network.add_node(Site::new(0.8, 0.0));
+        let south = network.add_node(Site::new(0.0, 0.8));
+
+        let euclidean = network
+            .nodes_around_site_iter(Site::default(), 1.0)
+            .copied()
+            .collect::<BTreeSet<_>>();
+        assert_eq!(euclidean, BTreeSet::from([east, south]));
+
+        // the distance along the x axis is doubled
+        let anisotropic = network
+            .nodes_around_site_iter_with(Site::default(), 1.0, |a, b| {
+                ((a.x - b.x) * 2.0).hypot(a.y - b.y)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(anisotropic, vec![south]);
+
+        let diagonal = network.add_node(Site::new(0.6, 0.3));
+        network.add_node(Site::new(0.6, 0.6));
+        let manhattan = network
+            .nodes_around_site_iter_with(Site::default(), 1.0, |a, b| {
+                (a.x - b.x).abs() + (a.y - b.y).abs()
+            })
+            .copied()
+            .collect::<BTreeSet<_>>();
+        assert_eq!(manhattan, BTreeSet::from([east, south, diagonal]));
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let mut network = PathNetwork::new();
+        assert!(network.convex_hull().is_empty());
+        for (x, y) in [(0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 2.0)] {
+            network.add_node(Site::new(x, y));
+        }
+        assert_eq!(
+            network.convex_hull(),
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(2.0, 0.0),
+                Site::new(2.0, 2.0),
+                Site::new(0.0, 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_occupancy_grid() {
+        let mut network = PathNetwork::new();
+        let nodes = [
+            (0.5, 0.5),
+            (3.5, 0.5),
+            (0.5, 1.5),
+            (2.5, 3.2),
+            (-2.0, 3.5),
+            (1.5, 3.5),
+        ]
+        .map(|(x, y)| network.add_node(Site::new(x, y)));
+        network.add_path(nodes[0], nodes[1]);
+        network.add_path(nodes[2], nodes[3]);
+        // partially outside the bounds
+        network.add_path(nodes[4], nodes[5]);
+
+        let grid = network.occupancy_grid((Site::new(4.0, 4.0), Site::new(0.0, 0.0)), 4, 4);
+        let occupied = (0..4)
+            .map(|row| {
+                (0..4)
+                    .map(|col| if grid[row * 4 + col] { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(occupied, vec!["####", "##..", ".##.", "###."]);
+
+        assert!(network
+            .occupancy_grid((Site::new(5.0, 5.0), Site::new(9.0, 9.0)), 2, 2)
+            .iter()
+            .all(|occupied| !occupied));
+        assert!(network
+            .occupancy_grid((Site::new(0.0, 0.0), Site::new(4.0, 4.0)), 0, 4)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_coverage() {
+        let mut network = PathNetwork::new();
+        let start = network.add_node(Site::new(0.0, 0.0));
+        let end = network.add_node(Site::new(4.0, 0.0));
+        network.add_path(start, end);
+
+        let sample_sites = [
+            (Site::new(2.0, 0.5), 3.0),
+            (Site::new(4.5, 0.5), 1.0),
+            (Site::new(2.0, 2.0), 4.0),
+        ];
+        assert_eq!(network.coverage(&sample_sites, 1.0), 0.5);
+        assert_eq!(network.coverage(&sample_sites, 0.6), 0.375);
+        assert_eq!(network.coverage(&sample_sites, 3.0), 1.0);
+        assert_eq!(network.coverage(&[], 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_to_block_polygons() {
+        let mut network = PathNetwork::new();
+        let nodes = [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (2.0, 1.0),
+            (3.0, 0.0),
+            (5.0, 0.0),
+            (5.1, 0.0),
+            (5.0, 0.1),
+        ]
+        .map(|(x, y)| network.add_node(Site::new(x, y)));
+        // two squares sharing a path
+        for (a, b) in [(0, 1), (1, 2), (3, 4), (4, 5), (0, 3), (1, 4), (2, 5)] {
+            network.add_path(nodes[a], nodes[b]);
+        }
+        // a dead end
+        network.add_path(nodes[2], nodes[6]);
+        // a sliver in another component
+        for (a, b) in [(7, 8), (8, 9), (9, 7)] {
+            network.add_path(nodes[a], nodes[b]);
+        }
+
+        let polygons = network.to_block_polygons(0.01);
+        assert_eq!(polygons.len(), 2);
+        polygons.iter().for_each(|polygon| {
+            assert_eq!(polygon.len(), 4);
+            assert!((signed_area(polygon) - 1.0).abs() < 1e-9);
+        });
+
+        assert_eq!(network.to_block_polygons(0.0).len(), 3);
+        // the unbounded faces are excluded even if `min_area` is negative
+        assert_eq!(network.to_block_polygons(-1.0).len(), 3);
+        assert!(PathNetwork::<Site>::new().to_block_polygons(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut network = PathNetwork::new();
+        assert_eq!(network.centroid(), None);
+
+        let hub = network.add_node(Site::new(0.0, 0.0));
+        for (x, y) in [(2.0, 0.0), (0.0, 2.0), (-2.0, 0.0)] {
+            let node_id = network.add_node(Site::new(x, y));
+            network.add_path(hub, node_id);
+        }
+        let isolated = network.add_node(Site::new(4.0, 2.0));
+
+        assert_eq!(network.centroid(), Some(Site::new(0.8, 0.8)));
+        // weighted by the degree
+        let degree = |node_id: NodeId, _: &Site| {
+            network
+                .neighbors_iter(node_id)
+                .map_or(0, |neighbors| neighbors.count()) as f64
+        };
+        assert_eq!(
+            network.weighted_center(degree),
+            Some(Site::new(0.0, 2.0 / 6.0))
+        );
+        assert_eq!(
+            network.weighted_center(|node_id, _| if node_id == isolated { 1.0 } else { 0.0 }),
+            Some(Site::new(4.0, 2.0))
+        );
+        assert_eq!(network.weighted_center(|_, _| 0.0), None);
+    }
+
+    #[test]
+    fn test_nearest_node_with() {
+        let mut network = PathNetwork::new();
+        let nodes = [(1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (-4.0, 0.0)]
+            .map(|(x, y)| network.add_node(Site::new(x, y)));
+        let far_left = |site: &Site| site.x < -1.0;
+
+        assert_eq!(
+            network.nearest_node_with(Site::default(), 5.0, |_| true),
+            Some(nodes[0])
+        );
+        assert_eq!(
+            network.nearest_node_with(Site::default(), 5.0, |site| site.x > 1.5),
+            Some(nodes[1])
+        );
+        assert_eq!(
+            network.nearest_node_with(Site::default(), 5.0, far_left),
+            Some(nodes[3])
+        );
+        assert_eq!(
+            network.nearest_node_with(Site::default(), 3.5, far_left),
+            None
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_weighted() {
+        // a detour (0 -> 1 -> 2 -> 3) with a fast road, and a shortcut (0 -> 4 -> 3) with a slow road
+        let mut network = PathNetwork::new();
+        let nodes = [(0.0, 0.0), (0.0, 2.0), (4.0, 2.0), (4.0, 0.0), (2.0, 0.0)]
+            .map(|(x, y)| network.add_node(Site::new(x, y)));
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (0, 4), (4, 3)] {
+            network.add_path(nodes[a], nodes[b]);
+        }
+        let isolated = network.add_node(Site::new(10.0, 10.0));
+
+        let distance = |a: &Site, b: &Site| a.distance(b);
+        let (path, cost) = network
+            .shortest_path_weighted(nodes[0], nodes[3], distance)
+            .unwrap();
+        assert_eq!(path, vec![nodes[0], nodes[4], nodes[3]]);
+        assert_eq!(cost, 4.0);
+
+        // the shortcut is 4 times slower
+        let travel_time = |a: &Site, b: &Site| {
+            let speed = if a.y == 0.0 && b.y == 0.0 { 1.0 } else { 4.0 };
+            a.distance(b) / speed
+        };
+        let (path, cost) = network
+            .shortest_path_weighted(nodes[0], nodes[3], travel_time)
+            .unwrap();
+        assert_eq!(path, vec![nodes[0], nodes[1], nodes[2], nodes[3]]);
+        assert_eq!(cost, 2.0);
+
+        assert_eq!(
+            network.shortest_path_weighted(nodes[0], nodes[0], distance),
+            Some((vec![nodes[0]], 0.0))
+        );
+        assert_eq!(
+            network.shortest_path_weighted(nodes[0], isolated, distance),
+            None
+        );
+        assert_eq!(
+            network.shortest_path_weighted(nodes[0], NodeId::new(100), distance),
+            None
+        );
+    }
+
+    #[test]
+    fn test_betweenness() {
+        // a square (0, 1, 2, 3) with a tail (3 -> 4 -> 5)
+        let mut network = PathNetwork::new();
+        let nodes = [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (-1.0, 1.0),
+            (-2.0, 1.0),
+        ]
+        .map(|(x, y)| network.add_node(Site::new(x, y)));
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (3, 0), (3, 4), (4, 5)] {
+            network.add_path(nodes[a], nodes[b]);
+        }
+
+        let betweenness = network.betweenness(None);
+        let expected = [1.5, 0.5, 1.5, 6.5, 4.0, 0.0];
+        for (node_id, expected) in nodes.iter().zip(expected) {
+            assert!((betweenness[node_id] - expected).abs() < 1e-9);
+        }
+
+        // from the end of the tail, node 4 is on all 4 shortest paths
+        let sampled = network.betweenness(Some(&[nodes[5], NodeId::new(100)]));
+        assert_eq!(sampled.len(), nodes.len());
+        assert!((sampled[&nodes[4]] - 4.0 * 6.0 * 0.5).abs() < 1e-9);
+        assert_eq!(sampled[&nodes[5]], 0.0);
+
+        assert!(network
+            .betweenness(Some(&[]))
+            .values()
+            .all(|value| *value == 0.0));
+    }
+
+    #[test]
+    fn test_replace_node() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 0.0));
+        network.add_path(node0, node1);
+        network.add_path(node2, node1);
+        let edge_id = network.edge_id(node0, node1);
+
+        assert_eq!(
+            network.replace_node(node1, Site::new(1.0, 3.0)),
+            Some(node1)
+        );
+        assert!(network.check_path_state_is_consistent());
+        assert_eq!(
+            network.search_nearest_node(Site::new(1.0, 2.9)),
+            Some(node1)
+        );
+        assert_eq!(network.edge_id(node0, node1), edge_id);
+        assert_eq!(
+            network
+                .crossings_with_segment(Site::new(0.0, 1.0), Site::new(2.0, 1.0))
+                .len(),
+            2
+        );
+        assert_eq!(
+            network.replace_node(NodeId::new(100), Site::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_topology_hash() {
+        let sites = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(x, y)| Site::new(x, y));
+        let create = |order: &[usize], paths: &[(usize, usize)]| {
+            let mut network = PathNetwork::new();
+            let mut node_ids = [NodeId::new(0); 4];
+            for i in order {
+                node_ids[*i] = network.add_node(sites[*i]);
+            }
+            for (a, b) in paths {
+                network.add_path(node_ids[*a], node_ids[*b]);
+            }
+            network
+        };
+
+        let hash = create(&[0, 1, 2, 3], &[(0, 1), (1, 2), (2, 3)]).topology_hash();
+        // the order of the insertions does not matter
+        assert_eq!(
+            create(&[3, 1, 0, 2], &[(3, 2), (1, 0), (2, 1)]).topology_hash(),
+            hash
+        );
+        // the paths are different
+        assert_ne!(
+            create(&[0, 1, 2, 3], &[(0, 1), (1, 2), (3, 0)]).topology_hash(),
+            hash
+        );
+        // the sites are different
+        let mut moved = create(&[0, 1, 2, 3], &[(0, 1), (1, 2), (2, 3)]);
+        moved.replace_node(NodeId::new(3), Site::new(0.0, 1.1));
+        assert_ne!(moved.topology_hash(), hash);
+        assert_ne!(PathNetwork::<Site>::new().topology_hash(), hash);
+    }
+
+    #[test]
+    fn test_ancestry() {
+        let mut network = PathNetwork::new();
+        let origin = network.add_node(Site::new(0.0, 0.0));
+        let removed = network.add_node(Site::new(0.5, 0.0));
+        let a = network.add_node(Site::new(1.0, 0.0));
+        let b = network.add_node(Site::new(2.0, 0.0));
+        let c = network.add_node(Site::new(1.0, 1.0));
+        network.set_parent(a, origin);
+        network.set_parent(b, a);
+        network.set_parent(c, a);
+
+        assert_eq!(network.ancestry(b), vec![b, a, origin]);
+        assert_eq!(network.ancestry(c), vec![c, a, origin]);
+        assert_eq!(network.ancestry(origin), vec![origin]);
+
+        // the chain is kept through the reconstruction
+        network.remove_node(removed);
+        let network = network.reconstruct().unwrap();
+        assert_eq!(
+            network.ancestry(NodeId::new(2)),
+            vec![NodeId::new(2), NodeId::new(1), NodeId::new(0)]
+        );
+        assert!(network.ancestry(NodeId::new(4)).is_empty());
+    }
+
+    #[test]
+    fn test_from_error() {
+        let nodes = vec![
+            Site::new(0.0, 0.0),
+            Site::new(1.0, 0.0),
+            Site::new(2.0, 0.0),
+        ];
+        assert!(PathNetwork::from(nodes.clone(), &[(0, 1), (1, 2)]).is_ok());
+        assert_eq!(
+            PathNetwork::from(nodes.clone(), &[(0, 1), (1, 3)]).unwrap_err(),
+            FromError::NodeIndexOutOfRange(3)
+        );
+        assert_eq!(
+            PathNetwork::from(nodes.clone(), &[(0, 1), (1, 0)]).unwrap_err(),
+            FromError::DuplicateEdge(1, 0)
+        );
+        assert_eq!(
+            PathNetwork::from(nodes.clone(), &[(2, 2)]).unwrap_err(),
+            FromError::SelfLoop(2)
+        );
+        assert!(PathNetwork::from_opt(nodes, &[(2, 2)]).is_none());
+    }
+
+    #[test]
+    fn test_edge_id() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(2.0, 0.0));
+        let node2 = network.add_node(Site::new(1.0, 0.0));
+
+        network.add_path(node0, node1);
+        let edge01 = network.edge_id(node0, node1).unwrap();
+        assert_eq!(network.edge_id(node1, node0), Some(edge01));
+        assert_eq!(network.edge_id(node0, node2), None);
+
+        // split the path by node2
+        network.remove_path(node0, node1);
+        network.add_path(node0, node2);
+        network.add_path(node2, node1);
+        assert_eq!(network.edge_id(node0, node1), None);
+
+        let edge02 = network.edge_id(node0, node2).unwrap();
+        let edge21 = network.edge_id(node2, node1).unwrap();
+        assert_ne!(edge02, edge01);
+        assert_ne!(edge21, edge01);
+        assert_ne!(edge02, edge21);
+
+        // adding the same path again does not change the id
+        network.add_path(node2, node0);
+        assert_eq!(network.edge_id(node0, node2), Some(edge02));
+    }
+
+    #[test]
+    fn test_isochrone() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 0.0));
+        let node2 = network.add_node(Site::new(1.0, 1.0));
+        let node3 = network.add_node(Site::new(0.0, 1.0));
+        let node4 = network.add_node(Site::new(5.0, 5.0));
+
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+        network.add_path(node2, node3);
+        network.add_path(node3, node4);
+
+        let reachable = network.reachable_within(node0, 2.0);
+        assert_eq!(reachable, vec![(node0, 0.0), (node1, 1.0), (node2, 2.0)]);
+
+        let isochrones = network.isochrone(node0, &[0.5, 2.0, 3.0]);
+        assert_eq!(isochrones.len(), 3);
+        assert_eq!(isochrones[0], vec![Site::new(0.0, 0.0)]);
+        assert_eq!(isochrones[1].len(), 3);
+        assert_eq!(
+            isochrones[2],
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(1.0, 0.0),
+                Site::new(1.0, 1.0),
+                Site::new(0.0, 1.0),
+            ]
+        );
+
+        assert!(network.isochrone(NodeId::new(100), &[1.0])[0].is_empty());
+    }
+
     fn xorshift(x: usize) -> usize {
         let mut x = x;
         x ^= x << 13;
@@ -634,4 +2846,11 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_path_network_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PathNetwork<Site>>();
+        assert_send_sync::<PathNetwork<crate::transport::node::TransportNode>>();
+    }
 }
@@ -12,6 +12,15 @@ where
     edges: BTreeMap<N, BTreeSet<N>>,
 }
 
+impl<N> Default for UndirectedGraph<N>
+where
+    N: UndirectedGraphNodeTrait,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<N> UndirectedGraph<N>
 where
     N: UndirectedGraphNodeTrait,
@@ -35,7 +44,7 @@ where
 
     /// Check if there is an edge between two nodes.
     pub fn has_edge(&self, a: N, b: N) -> bool {
-        self.edges.get(&a).map_or(false, |set| set.contains(&b))
+        self.edges.get(&a).is_some_and(|set| set.contains(&b))
     }
 
     /// Remove an edge from the graph.
@@ -56,19 +65,27 @@ where
         Some((a, b))
     }
 
-    /// Get the number of nodes in the graph.
-    /// This function is now only for testing, but it may be useful in the future.
-    #[cfg(test)]
-    fn order(&self) -> usize {
+    /// Get the number of nodes which have at least one edge.
+    pub fn node_count(&self) -> usize {
         self.edges.len()
     }
 
-    #[cfg(test)]
     /// Get the number of edges in the graph.
-    pub fn size(&self) -> usize {
+    pub fn edge_count(&self) -> usize {
         self.edges.values().map(|set| set.len()).sum::<usize>() / 2
     }
 
+    /// Get the density of the graph, which is the ratio of the edges to the possible edges between the nodes.
+    ///
+    /// Return 0.0 if the graph has less than 2 nodes.
+    pub fn density(&self) -> f64 {
+        let node_count = self.node_count();
+        if node_count < 2 {
+            return 0.0;
+        }
+        2.0 * self.edge_count() as f64 / (node_count * (node_count - 1)) as f64
+    }
+
     /// Get the neighbors of a node as an iterator.
     pub fn neighbors_iter(&self, node: N) -> Option<impl Iterator<Item = &N> + '_> {
         self.edges.get(&node).map(|set| set.iter())
@@ -76,12 +93,14 @@ where
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::map_clone)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_undirected_graph() {
         let mut graph = UndirectedGraph::new();
+        assert_eq!(graph.density(), 0.0);
 
         graph.add_edge(103, 25);
         graph.add_edge(85, 103);
@@ -96,8 +115,9 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(neighbors, vec![25, 85]);
 
-        assert_eq!(graph.order(), 5);
-        assert_eq!(graph.size(), 4);
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.density(), 0.4);
         assert_eq!(graph.has_edge(103, 25), true);
         assert_eq!(graph.has_edge(25, 103), true);
         assert_eq!(graph.has_edge(85, 103), true);
@@ -107,16 +127,16 @@ mod tests {
 
         graph.remove_edge(103, 25);
 
-        assert_eq!(graph.order(), 5);
-        assert_eq!(graph.size(), 3);
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 3);
         assert_eq!(graph.has_edge(103, 25), false);
         assert_eq!(graph.has_edge(25, 103), false);
         assert_eq!(graph.has_edge(85, 103), true);
 
         graph.remove_edge(85, 103);
 
-        assert_eq!(graph.order(), 4);
-        assert_eq!(graph.size(), 2);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 2);
         assert_eq!(graph.has_edge(85, 103), false);
         assert_eq!(graph.has_edge(103, 85), false);
         assert_eq!(graph.has_edge(85, 32), true);
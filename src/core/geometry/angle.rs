@@ -22,17 +22,21 @@ impl Angle {
         self.0
     }
 
-    /// Get opposite angle.
+    /// Get the opposite angle, which is rotated by PI.
     pub fn opposite(&self) -> Self {
         Self::new(self.0 + std::f64::consts::PI)
     }
 
-    /// Get the clockwise right angle.
+    /// Get the clockwise right angle, which is rotated clockwise by PI/2 (e.g. the right side of the direction).
+    ///
+    /// This is the inverse of `right_counterclockwise`.
     pub fn right_clockwise(&self) -> Self {
         Self::new(self.0 + 0.5 * std::f64::consts::PI)
     }
 
-    /// Get the counterclockwise right angle.
+    /// Get the counterclockwise right angle, which is rotated counterclockwise by PI/2 (e.g. the left side of the direction).
+    ///
+    /// This is the inverse of `right_clockwise`.
     pub fn right_counterclockwise(&self) -> Self {
         Self::new(self.0 - 0.5 * std::f64::consts::PI)
     }
@@ -99,6 +103,10 @@ impl Angle {
     }
 
     /// Create an iterator of angles around the specified angle.
+    ///
+    /// The `step_num` angles are evenly spaced within `radian_range` centered at the angle,
+    /// including both ends, in clockwise order.
+    /// If `step_num` is 1 or `radian_range` is 0, only the specified angle is yielded.
     pub fn iter_range_around(&self, radian_range: f64, step_num: usize) -> AngleIter {
         if step_num == 1 || radian_range == 0.0 {
             return AngleIter {
@@ -156,6 +164,29 @@ mod tests {
         assert_eq!(Angle::new(-2.0 * std::f64::consts::PI).0, 0.0);
     }
 
+    #[test]
+    fn test_angle_rotations() {
+        let angles =
+            [-0.75, -0.5, 0.0, 0.25, 0.5, 1.0].map(|r| Angle::new(r * std::f64::consts::PI));
+        for angle in angles {
+            let opposite = angle.opposite();
+            assert!((opposite.unit_x() + angle.unit_x()).abs() < 1e-9);
+            assert!((opposite.unit_y() + angle.unit_y()).abs() < 1e-9);
+            assert!((opposite.opposite().unit_x() - angle.unit_x()).abs() < 1e-9);
+
+            let restored = angle.right_clockwise().right_counterclockwise();
+            assert!((restored.unit_x() - angle.unit_x()).abs() < 1e-9);
+            assert!((restored.unit_y() - angle.unit_y()).abs() < 1e-9);
+        }
+
+        // the angle 0 points to the negative y-axis, and the clockwise right is the positive x-axis
+        let right = Angle::new(0.0).right_clockwise();
+        assert!((right.unit_x() - 1.0).abs() < 1e-9 && right.unit_y().abs() < 1e-9);
+        let left = Angle::new(0.0).right_counterclockwise();
+        assert!((left.unit_x() + 1.0).abs() < 1e-9 && left.unit_y().abs() < 1e-9);
+        assert_eq!(Angle::new(0.0).opposite().radian(), std::f64::consts::PI);
+    }
+
     #[test]
     fn test_angle_diff_clockwise_to() {
         assert_eq!(
@@ -237,4 +268,29 @@ mod tests {
         assert_eq!(iter.next(), Some(Angle::new(2.0 * std::f64::consts::PI)));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_angle_iter_range_around() {
+        let angle = Angle::new(std::f64::consts::PI * 0.25);
+        let angles = angle
+            .iter_range_around(std::f64::consts::PI * 0.5, 1)
+            .collect::<Vec<_>>();
+        assert_eq!(angles, vec![angle]);
+        assert!(!angles[0].radian().is_nan());
+
+        let angles = angle
+            .iter_range_around(std::f64::consts::PI * 0.5, 3)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            angles,
+            vec![
+                Angle::new(0.0),
+                angle,
+                Angle::new(std::f64::consts::PI * 0.5)
+            ]
+        );
+
+        assert_eq!(angle.iter_range_around(0.0, 5).count(), 1);
+        assert_eq!(angle.iter_range_around(std::f64::consts::PI, 0).count(), 0);
+    }
 }
@@ -45,6 +45,36 @@ impl LineSegment {
         Some(Site::new(x, y))
     }
 
+    /// Calculate the overlapping portion of two collinear line segments.
+    /// If the line segments are not collinear, or they share only a point, return None.
+    pub fn overlap(&self, other: &Self) -> Option<LineSegment> {
+        let direction = (self.1.x - self.0.x, self.1.y - self.0.y);
+        let length_2 = direction.0 * direction.0 + direction.1 * direction.1;
+        if length_2 == 0.0 {
+            return None;
+        }
+
+        // The parameter of the site on the line (0 at the start, 1 at the end),
+        // or None if the site is not on the line.
+        let parameter = |site: &Site| -> Option<f64> {
+            let relative = (site.x - self.0.x, site.y - self.0.y);
+            let cross = direction.0 * relative.1 - direction.1 * relative.0;
+            if cross.abs() > length_2 * 1e-9 {
+                return None;
+            }
+            Some((direction.0 * relative.0 + direction.1 * relative.1) / length_2)
+        };
+        let (t0, t1) = (parameter(&other.0)?, parameter(&other.1)?);
+
+        let start = t0.min(t1).max(0.0);
+        let end = t0.max(t1).min(1.0);
+        if start >= end {
+            return None;
+        }
+        let site_at = |t: f64| Site::new(self.0.x + direction.0 * t, self.0.y + direction.1 * t);
+        Some(LineSegment::new(site_at(start), site_at(end)))
+    }
+
     /// Calculate the perpendicular projection of the site on the line segment.
     /// If the projection is outside the line segment, return None.
     pub fn get_projection(&self, site: &Site) -> Option<Site> {
@@ -131,6 +161,33 @@ mod tests {
         assert_eq!(line0.get_intersection(&line1), Some(Site::new(1.4, 3.2)));
     }
 
+    #[test]
+    fn test_overlap() {
+        // Collinear overlapping lines
+        let line0 = LineSegment::new(Site::new(1.0, 1.0), Site::new(3.0, 3.0));
+        let line1 = LineSegment::new(Site::new(4.0, 4.0), Site::new(2.0, 2.0));
+        assert_eq!(
+            line0.overlap(&line1),
+            Some(LineSegment::new(Site::new(2.0, 2.0), Site::new(3.0, 3.0)))
+        );
+
+        // One line contains the other
+        let line1 = LineSegment::new(Site::new(1.5, 1.5), Site::new(2.0, 2.0));
+        assert_eq!(line0.overlap(&line1), Some(line1.clone()));
+
+        // Collinear lines that barely touch by their edges
+        let line1 = LineSegment::new(Site::new(3.0, 3.0), Site::new(4.0, 4.0));
+        assert_eq!(line0.overlap(&line1), None);
+
+        // Parallel lines
+        let line1 = LineSegment::new(Site::new(1.0, 2.0), Site::new(3.0, 4.0));
+        assert_eq!(line0.overlap(&line1), None);
+
+        // Intersecting lines
+        let line1 = LineSegment::new(Site::new(1.0, 3.0), Site::new(3.0, 1.0));
+        assert_eq!(line0.overlap(&line1), None);
+    }
+
     #[test]
     fn test_get_projection() {
         let line = LineSegment::new(Site::new(1.0, 1.0), Site::new(3.0, 3.0));
@@ -1,3 +1,5 @@
 pub mod angle;
 pub mod line_segment;
+pub mod polygon;
 pub mod site;
+pub mod triangulation;
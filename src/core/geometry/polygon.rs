@@ -0,0 +1,258 @@
+use super::{line_segment::LineSegment, site::Site};
+
+/// Calculate the cross product of the vectors `o -> a` and `o -> b`.
+fn cross(o: &Site, a: &Site, b: &Site) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Calculate the convex hull of sites.
+///
+/// The hull is returned as a ring of sites without repeating the first site at the end.
+/// Collinear sites on the boundary are omitted.
+/// If there are less than 3 distinct sites, the distinct sites are returned as they are.
+pub fn convex_hull(sites: &[Site]) -> Vec<Site> {
+    let mut sites = sites.to_vec();
+    sites.sort();
+    sites.dedup();
+
+    if sites.len() < 3 {
+        return sites;
+    }
+
+    // Andrew's monotone chain algorithm
+    let mut hull: Vec<Site> = Vec::with_capacity(sites.len() * 2);
+    for site in sites.iter() {
+        while hull.len() >= 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], site) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(*site);
+    }
+    let lower_len = hull.len() + 1;
+    for site in sites.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], site) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(*site);
+    }
+    hull.pop();
+    hull
+}
+
+/// Calculate the signed area of a polygon by the shoelace formula.
+///
+/// The polygon is a ring of sites without repeating the first site at the end.
+/// The area is positive if the sites are ordered from the positive x axis to the positive y axis.
+pub fn signed_area(sites: &[Site]) -> f64 {
+    let n = sites.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (sites[i], sites[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+/// Boundary represented by a polygon.
+///
+/// The polygon is a ring of sites without repeating the first site at the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boundary {
+    sites: Vec<Site>,
+}
+
+impl Boundary {
+    /// Create a boundary from the vertices of the polygon.
+    pub fn new(sites: Vec<Site>) -> Self {
+        Self { sites }
+    }
+
+    /// Get the vertices of the polygon.
+    pub fn sites(&self) -> &[Site] {
+        &self.sites
+    }
+
+    /// Check if the site is inside the polygon.
+    ///
+    /// Sites exactly on the edges may be treated as either inside or outside.
+    pub fn contains(&self, site: &Site) -> bool {
+        // ray casting to the positive x direction
+        let mut inside = false;
+        for (i, a) in self.sites.iter().enumerate() {
+            let b = &self.sites[(i + 1) % self.sites.len()];
+            if (a.y > site.y) != (b.y > site.y) {
+                let x = a.x + (site.y - a.y) * (b.x - a.x) / (b.y - a.y);
+                if site.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Trim the line segment from `a` to `b` to the portions inside the polygon.
+    ///
+    /// The portions are returned in order from `a`. Portions with zero length are omitted.
+    pub fn clip_segment_parts(&self, a: Site, b: Site) -> Vec<(Site, Site)> {
+        let segment = LineSegment::new(a, b);
+        let length = a.distance(&b);
+        if length == 0.0 {
+            return Vec::new();
+        }
+
+        // parameters (0 at `a`, 1 at `b`) of the crossings with the edges
+        let mut parameters = vec![0.0, 1.0];
+        for (i, vertex) in self.sites.iter().enumerate() {
+            let edge = LineSegment::new(*vertex, self.sites[(i + 1) % self.sites.len()]);
+            if let Some(crossing) = segment.get_intersection(&edge) {
+                parameters.push((a.distance(&crossing) / length).clamp(0.0, 1.0));
+            }
+        }
+        parameters.sort_by(f64::total_cmp);
+        parameters.dedup();
+
+        let site_at = |t: f64| Site::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+        let mut parts: Vec<(Site, Site)> = Vec::new();
+        for pair in parameters.windows(2) {
+            if !self.contains(&site_at((pair[0] + pair[1]) / 2.0)) {
+                continue;
+            }
+            let (start, end) = (site_at(pair[0]), site_at(pair[1]));
+            // join with the previous portion if they are continuous
+            match parts.last_mut() {
+                Some(last) if last.1 == start => last.1 = end,
+                _ => parts.push((start, end)),
+            }
+        }
+        parts
+    }
+
+    /// Trim the line segment from `a` to `b` to the polygon.
+    ///
+    /// If the segment enters the polygon more than once (for concave polygons),
+    /// only the first portion from `a` is returned. See `clip_segment_parts` to get all portions.
+    /// Return `None` if the segment is completely outside the polygon.
+    pub fn clip_segment(&self, a: Site, b: Site) -> Option<(Site, Site)> {
+        self.clip_segment_parts(a, b).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_area() {
+        let square = [
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(2.0, 2.0),
+            Site::new(0.0, 2.0),
+        ];
+        assert_eq!(signed_area(&square), 4.0);
+        let reversed = square.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(signed_area(&reversed), -4.0);
+        assert_eq!(signed_area(&square[..2]), 0.0);
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let sites = vec![
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(1.0, 1.0),
+            Site::new(2.0, 2.0),
+            Site::new(0.0, 2.0),
+            Site::new(1.0, 0.0),
+            Site::new(0.0, 0.0),
+        ];
+        let hull = convex_hull(&sites);
+        assert_eq!(
+            hull,
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(2.0, 0.0),
+                Site::new(2.0, 2.0),
+                Site::new(0.0, 2.0),
+            ]
+        );
+
+        let hull = convex_hull(&[Site::new(1.0, 1.0), Site::new(1.0, 1.0)]);
+        assert_eq!(hull, vec![Site::new(1.0, 1.0)]);
+
+        // collinear sites
+        let hull = convex_hull(&[
+            Site::new(0.0, 0.0),
+            Site::new(1.0, 1.0),
+            Site::new(2.0, 2.0),
+        ]);
+        assert_eq!(hull, vec![Site::new(0.0, 0.0), Site::new(2.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_boundary_contains() {
+        // L-shaped polygon
+        let boundary = Boundary::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(2.0, 1.0),
+            Site::new(1.0, 1.0),
+            Site::new(1.0, 2.0),
+            Site::new(0.0, 2.0),
+        ]);
+        assert!(boundary.contains(&Site::new(0.5, 0.5)));
+        assert!(boundary.contains(&Site::new(1.5, 0.5)));
+        assert!(boundary.contains(&Site::new(0.5, 1.5)));
+        assert!(!boundary.contains(&Site::new(1.5, 1.5)));
+        assert!(!boundary.contains(&Site::new(-0.5, 0.5)));
+
+        assert!(!Boundary::new(Vec::new()).contains(&Site::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_boundary_clip_segment() {
+        // U-shaped polygon (concave)
+        let boundary = Boundary::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(3.0, 0.0),
+            Site::new(3.0, 3.0),
+            Site::new(2.0, 3.0),
+            Site::new(2.0, 1.0),
+            Site::new(1.0, 1.0),
+            Site::new(1.0, 3.0),
+            Site::new(0.0, 3.0),
+        ]);
+
+        // straddling the border
+        assert_eq!(
+            boundary.clip_segment(Site::new(-1.0, 0.5), Site::new(1.5, 0.5)),
+            Some((Site::new(0.0, 0.5), Site::new(1.5, 0.5)))
+        );
+        // crossing the gap of the U
+        let a = Site::new(-1.0, 2.0);
+        let b = Site::new(4.0, 2.0);
+        assert_eq!(
+            boundary.clip_segment_parts(a, b),
+            vec![
+                (Site::new(0.0, 2.0), Site::new(1.0, 2.0)),
+                (Site::new(2.0, 2.0), Site::new(3.0, 2.0))
+            ]
+        );
+        assert_eq!(
+            boundary.clip_segment(a, b),
+            Some((Site::new(0.0, 2.0), Site::new(1.0, 2.0)))
+        );
+        // inside the gap
+        assert_eq!(
+            boundary.clip_segment(Site::new(1.2, 2.0), Site::new(1.8, 2.5)),
+            None
+        );
+        // completely inside
+        assert_eq!(
+            boundary.clip_segment(Site::new(0.5, 0.5), Site::new(2.5, 0.5)),
+            Some((Site::new(0.5, 0.5), Site::new(2.5, 0.5)))
+        );
+    }
+}
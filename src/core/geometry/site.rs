@@ -1,3 +1,5 @@
+use std::ops::{Add, Mul, Sub};
+
 use rstar::{PointDistance, RTreeObject, AABB};
 
 use super::angle::Angle;
@@ -54,6 +56,30 @@ impl PointDistance for Site {
     }
 }
 
+impl Add for Site {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Site {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Site {
+    type Output = Self;
+
+    fn mul(self, scale: f64) -> Self {
+        Self::new(self.x * scale, self.y * scale)
+    }
+}
+
 impl Site {
     /// Create a site from x and y coordinates.
     pub fn new(x: f64, y: f64) -> Self {
@@ -84,6 +110,23 @@ impl Site {
         Self::new(x, y)
     }
 
+    /// Calculate the dot product, regarding the sites as vectors.
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Calculate the z component of the cross product, regarding the sites as vectors.
+    ///
+    /// The result is positive if `other` is on the side of the positive y axis from the positive x axis of `self`.
+    pub fn cross(&self, other: &Self) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Calculate the euclidean norm, regarding the site as a vector.
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
     /// Calculate the angle to the other site.
     pub fn get_angle(&self, other: &Self) -> Angle {
         let dx = other.x - self.x;
@@ -106,6 +149,19 @@ mod tests {
         assert_eq!(site1.distance(&site2), 5.0);
     }
 
+    #[test]
+    fn test_arithmetic() {
+        let a = Site::new(1.0, 2.0);
+        let b = Site::new(3.0, -1.0);
+        assert_eq!(a + b, Site::new(4.0, 1.0));
+        assert_eq!(a - b, Site::new(-2.0, 3.0));
+        assert_eq!(a * 2.0, Site::new(2.0, 4.0));
+        assert_eq!(a.dot(&b), 1.0);
+        assert_eq!(a.cross(&b), -7.0);
+        assert_eq!(b.cross(&a), 7.0);
+        assert_eq!(Site::new(3.0, 4.0).norm(), 5.0);
+    }
+
     #[test]
     fn test_extend() {
         let site = Site::new(0.0, 0.0);
@@ -0,0 +1,187 @@
+use super::site::Site;
+
+/// Triangle with the circumcircle used in the triangulation.
+struct Triangle {
+    vertices: [usize; 3],
+    center: Site,
+    radius_2: f64,
+}
+
+impl Triangle {
+    fn new(vertices: [usize; 3], sites: &[Site]) -> Self {
+        let (a, b, c) = (sites[vertices[0]], sites[vertices[1]], sites[vertices[2]]);
+        let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+        let (a2, b2, c2) = (
+            a.x * a.x + a.y * a.y,
+            b.x * b.x + b.y * b.y,
+            c.x * c.x + c.y * c.y,
+        );
+        let center = Site::new(
+            (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+            (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d,
+        );
+        Self {
+            vertices,
+            center,
+            radius_2: center.distance_2(&a),
+        }
+    }
+
+    /// Check if the site is inside the circumcircle.
+    /// Degenerated (collinear) triangles are treated as containing every site.
+    fn circumcircle_contains(&self, site: &Site) -> bool {
+        !self.radius_2.is_finite() || self.center.distance_2(site) < self.radius_2
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Get the ordered pair of indices to identify an edge.
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// Calculate the edges of the Delaunay triangulation of sites (Bowyer-Watson algorithm).
+///
+/// Each edge is returned once as a pair of indices of `sites` (the smaller index first), sorted in ascending order.
+/// Duplicated sites are ignored except for the first one.
+/// If all sites are collinear, the edges between adjacent sites on the line are returned.
+pub fn delaunay_edges(sites: &[Site]) -> Vec<(usize, usize)> {
+    // ignore duplicated sites
+    let indices = {
+        let mut indices = (0..sites.len()).collect::<Vec<_>>();
+        indices.sort_by(|a, b| sites[*a].cmp(&sites[*b]).then(a.cmp(b)));
+        indices.dedup_by(|a, b| sites[*a] == sites[*b]);
+        indices
+    };
+    if indices.len() < 2 {
+        return Vec::new();
+    }
+
+    let (min_x, max_x, min_y, max_y) = indices.iter().fold(
+        (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(min_x, max_x, min_y, max_y), i| {
+            let site = sites[*i];
+            (
+                min_x.min(site.x),
+                max_x.max(site.x),
+                min_y.min(site.y),
+                max_y.max(site.y),
+            )
+        },
+    );
+    let span = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let center = Site::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+
+    // the sites and the vertices of the super triangle which contains all sites
+    let mut vertices = sites.to_vec();
+    let super_start = vertices.len();
+    vertices.push(Site::new(center.x - 20.0 * span, center.y - span));
+    vertices.push(Site::new(center.x, center.y + 20.0 * span));
+    vertices.push(Site::new(center.x + 20.0 * span, center.y - span));
+
+    let mut triangles = vec![Triangle::new(
+        [super_start, super_start + 1, super_start + 2],
+        &vertices,
+    )];
+
+    for i in indices {
+        let site = vertices[i];
+        let (bad, good): (Vec<_>, Vec<_>) = triangles
+            .into_iter()
+            .partition(|triangle| triangle.circumcircle_contains(&site));
+        triangles = good;
+
+        // the boundary of the polygonal hole is the edges which are not shared by bad triangles
+        let mut edges = bad
+            .iter()
+            .flat_map(|triangle| triangle.edges())
+            .collect::<Vec<_>>();
+        edges.sort_by_key(|(a, b)| edge_key(*a, *b));
+        let boundary = edges
+            .iter()
+            .filter(|(a, b)| {
+                edges
+                    .iter()
+                    .filter(|(c, d)| edge_key(*a, *b) == edge_key(*c, *d))
+                    .count()
+                    == 1
+            })
+            .collect::<Vec<_>>();
+
+        boundary.into_iter().for_each(|(a, b)| {
+            triangles.push(Triangle::new([*a, *b, i], &vertices));
+        });
+    }
+
+    let mut edges = triangles
+        .iter()
+        .flat_map(|triangle| triangle.edges())
+        .filter(|(a, b)| *a < super_start && *b < super_start)
+        .map(|(a, b)| edge_key(a, b))
+        .collect::<Vec<_>>();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delaunay_edges() {
+        // a square with a center
+        let sites = vec![
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(2.0, 2.0),
+            Site::new(0.0, 2.0),
+            Site::new(1.0, 1.0),
+        ];
+        assert_eq!(
+            delaunay_edges(&sites),
+            vec![
+                (0, 1),
+                (0, 3),
+                (0, 4),
+                (1, 2),
+                (1, 4),
+                (2, 3),
+                (2, 4),
+                (3, 4)
+            ]
+        );
+
+        // the shorter diagonal is chosen
+        let sites = vec![
+            Site::new(0.0, 0.0),
+            Site::new(4.0, 0.0),
+            Site::new(2.0, 1.0),
+            Site::new(2.0, -1.0),
+        ];
+        let edges = delaunay_edges(&sites);
+        assert!(edges.contains(&(2, 3)));
+        assert!(!edges.contains(&(0, 1)));
+        assert_eq!(edges.len(), 5);
+
+        // collinear and duplicated sites
+        let sites = vec![
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(1.0, 0.0),
+            Site::new(1.0, 0.0),
+        ];
+        assert_eq!(delaunay_edges(&sites), vec![(0, 2), (1, 2)]);
+
+        assert!(delaunay_edges(&[Site::new(0.0, 0.0)]).is_empty());
+    }
+}
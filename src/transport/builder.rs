@@ -1,8 +1,12 @@
-use std::collections::BinaryHeap;
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
 
 use crate::core::{
-    container::path_network::{NodeId, PathNetwork},
-    geometry::{angle::Angle, line_segment::LineSegment, site::Site},
+    container::path_network::{EdgeId, NodeId, PathNetwork},
+    geometry::{angle::Angle, line_segment::LineSegment, polygon::Boundary, site::Site},
 };
 
 use super::{
@@ -10,29 +14,294 @@ use super::{
         growth_type::{BridgeNodeType, GrowthTypes, NextNodeType},
         stump::Stump,
     },
-    node::TransportNode,
-    params::{metrics::PathMetrics, numeric::Stage},
+    node::{TransportNode, TransportNodeTrait},
+    params::{
+        construction::PathConstructionFactors,
+        metrics::PathMetrics,
+        numeric::Stage,
+        priority::PathPrioritizationFactors,
+        rules::{GrowthMode, RulesError, TransportRules},
+        style::StageStyle,
+    },
     traits::{PathPrioritizator, RandomF64Provider, TerrainProvider, TransportRulesProvider},
 };
 
-pub struct TransportBuilder<'a, RP, TP, PP>
+pub use super::growth::stump::ProbeResult;
+
+/// RandomF64Provider which always provides the middle value of [0, 1).
+///
+/// This is used to create stumps where no randomness is expected (e.g. the first stumps from origins).
+struct MiddleRandom;
+
+impl RandomF64Provider for MiddleRandom {
+    fn gen_f64(&mut self) -> f64 {
+        0.5
+    }
+}
+
+/// RandomF64Provider which generates a reproducible sequence from a seed (SplitMix64).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    /// Create a new `SeededRandom` from the seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl RandomF64Provider for SeededRandom {
+    fn gen_f64(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // the upper 53 bits are used to generate a value in [0, 1)
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Policy to handle sites where the terrain provider returns no elevation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingElevationPolicy {
+    /// Sites without elevation are not used to create paths.
+    #[default]
+    Reject,
+    /// Sites without elevation are treated as flat ground with the specified elevation.
+    Flat(f64),
+    /// Sites without elevation are treated as having the elevation of the node from which the path is extended.
+    ///
+    /// The origins are rejected if they have no elevation.
+    ExtrapolateLast,
+}
+
+/// Rule to assign the stages of the origins from the slope of the terrain.
+///
+/// The stage is the number of the thresholds which the slope around the origin exceeds.
+/// For example, with the thresholds `[0.1, 0.3]`, origins on flat ground (e.g. valleys) become trunk paths (stage 0)
+/// and origins on steeper slopes become local paths (stage 1 or 2).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StageFromTerrain {
+    /// Thresholds of the slope (the elevation difference per distance).
+    pub slope_thresholds: Vec<f64>,
+}
+
+impl StageFromTerrain {
+    /// Create a new `StageFromTerrain`.
+    pub fn new(slope_thresholds: Vec<f64>) -> Self {
+        Self { slope_thresholds }
+    }
+
+    /// Get the stage from the slope. The sign of the slope is ignored.
+    pub fn stage(&self, slope: f64) -> Stage {
+        let slope = slope.abs();
+        Stage::from_num(
+            self.slope_thresholds
+                .iter()
+                .filter(|threshold| slope > **threshold)
+                .count(),
+        )
+    }
+}
+
+/// Waypoints which attract the growth of the paths.
+#[derive(Debug, Clone, PartialEq)]
+struct Waypoints {
+    sites: Vec<Site>,
+    attract_radius: f64,
+    strength: f64,
+}
+
+impl Waypoints {
+    /// Get the bias of the priority of the path from `site_start` to `site_end`.
+    ///
+    /// The bias is `strength` multiplied by the cosine of the angle between the path
+    /// and the direction to the nearest waypoint within `attract_radius` from the start of the path.
+    fn bias(&self, site_start: Site, site_end: Site) -> f64 {
+        let nearest = self
+            .sites
+            .iter()
+            .map(|site| (*site, site_start.distance(site)))
+            .filter(|(_, distance)| *distance <= self.attract_radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        let (waypoint, distance) = if let Some(nearest) = nearest {
+            nearest
+        } else {
+            return 0.0;
+        };
+        let direction = site_end - site_start;
+        let length = direction.norm();
+        if distance == 0.0 || length == 0.0 {
+            return 0.0;
+        }
+        self.strength * direction.dot(&(waypoint - site_start)) / (length * distance)
+    }
+}
+
+/// PathPrioritizator which adds the bias toward the waypoints to the priorities.
+struct WaypointPrioritizator<'a, PP>
+where
+    PP: PathPrioritizator + ?Sized,
+{
+    path_prioritizator: &'a PP,
+    waypoints: Option<&'a Waypoints>,
+}
+
+impl<PP> PathPrioritizator for WaypointPrioritizator<'_, PP>
+where
+    PP: PathPrioritizator + ?Sized,
+{
+    fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+        let bias = self.waypoints.map_or(0.0, |waypoints| {
+            waypoints.bias(factors.site_start, factors.site_end)
+        });
+        Some(self.path_prioritizator.prioritize(factors)? + bias)
+    }
+}
+
+/// TerrainProvider which fills missing elevations by `MissingElevationPolicy`.
+///
+/// Sites outside `bounds` or `boundary` have no elevation regardless of the policy.
+struct PolicyTerrainProvider<'a, TP>
+where
+    TP: TerrainProvider + ?Sized,
+{
+    terrain_provider: &'a TP,
+    policy: MissingElevationPolicy,
+    last_elevation: Option<f64>,
+    bounds: Option<(Site, Site)>,
+    boundary: Option<&'a Boundary>,
+}
+
+impl<TP> TerrainProvider for PolicyTerrainProvider<'_, TP>
+where
+    TP: TerrainProvider + ?Sized,
+{
+    fn get_elevation(&self, site: &Site) -> Option<f64> {
+        if let Some((min, max)) = self.bounds {
+            if site.x < min.x || site.x > max.x || site.y < min.y || site.y > max.y {
+                return None;
+            }
+        }
+        if self
+            .boundary
+            .is_some_and(|boundary| !boundary.contains(site))
+        {
+            return None;
+        }
+        if let Some(elevation) = self.terrain_provider.get_elevation(site) {
+            return Some(elevation);
+        }
+        match self.policy {
+            MissingElevationPolicy::Reject => None,
+            MissingElevationPolicy::Flat(elevation) => Some(elevation),
+            MissingElevationPolicy::ExtrapolateLast => self.last_elevation,
+        }
+    }
+}
+
+/// Result of adding the first stumps from an origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedResult {
+    /// Whether the stump in the specified direction is added.
+    pub forward: bool,
+    /// Whether the stump in the opposite direction is added.
+    pub backward: bool,
+}
+
+/// Changes of the path network in a step of the builder.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GrowthOutcome {
+    /// Nodes added to the network.
+    pub added_nodes: Vec<NodeId>,
+    /// Paths added to the network.
+    pub added_paths: Vec<(NodeId, NodeId)>,
+    /// Paths removed from the network (split by the added paths at intersections).
+    pub removed_paths: Vec<(NodeId, NodeId)>,
+}
+
+/// Cumulative counts of the changes of the path network made by the builder.
+///
+/// Since the paths are split at intersections, the number of the paths added can be larger than
+/// the number of the paths in the final network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildCounters {
+    /// Number of the nodes created.
+    pub nodes_created: usize,
+    /// Number of the paths added.
+    pub edges_added: usize,
+    /// Number of the paths removed (split by the added paths at intersections).
+    pub edges_removed: usize,
+}
+
+/// Callback called after a node is added to the network.
+type NodeAddedCallback<'a> = Box<dyn FnMut(NodeId, &TransportNode) + 'a>;
+
+/// Callback called after a path is added to the network.
+type PathAddedCallback<'a> = Box<dyn FnMut((NodeId, NodeId)) + 'a>;
+
+/// Draw a random value from the provider, checking the contract of `RandomF64Provider::gen_f64` in debug builds.
+fn draw_random<R>(rng: &mut R) -> f64
+where
+    R: RandomF64Provider,
+{
+    let value = rng.gen_f64();
+    debug_assert!(
+        (0.0..1.0).contains(&value),
+        "RandomF64Provider::gen_f64 must return a value in [0, 1), but returned {}",
+        value
+    );
+    value
+}
+
+/// `TransportBuilder` whose providers are trait objects.
+///
+/// This can be stored in a struct field even if the types of the providers are known only at runtime.
+pub type DynTransportBuilder<'a> = TransportBuilder<
+    'a,
+    dyn TransportRulesProvider + 'a,
+    dyn TerrainProvider + 'a,
+    dyn PathPrioritizator + 'a,
+>;
+
+/// Builder of the path network.
+///
+/// The nodes of the network are `N`, which is `TransportNode` by default (see `TransportNodeTrait` and `with_node_type`).
+pub struct TransportBuilder<'a, RP, TP, PP, N = TransportNode>
 where
-    RP: TransportRulesProvider,
-    TP: TerrainProvider,
-    PP: PathPrioritizator,
+    RP: TransportRulesProvider + ?Sized,
+    TP: TerrainProvider + ?Sized,
+    PP: PathPrioritizator + ?Sized,
+    N: TransportNodeTrait,
 {
-    path_network: PathNetwork<TransportNode>,
+    path_network: PathNetwork<N>,
     rules_provider: &'a RP,
     terrain_provider: &'a TP,
     path_prioritizator: &'a PP,
     stump_heap: BinaryHeap<Stump>,
+    missing_elevation_policy: MissingElevationPolicy,
+    bounds: Option<(Site, Site)>,
+    boundary: Option<Boundary>,
+    edge_taper: Option<(f64, f64)>,
+    construction_factors: BTreeMap<EdgeId, PathConstructionFactors>,
+    on_node_added: Option<NodeAddedCallback<'a>>,
+    on_path_added: Option<PathAddedCallback<'a>>,
+    outcome: GrowthOutcome,
+    counters: BuildCounters,
+    stage_styles: BTreeMap<Stage, StageStyle>,
+    stage_from_terrain: Option<StageFromTerrain>,
+    waypoints: Option<Waypoints>,
+    rules_error: Cell<Option<RulesError>>,
 }
 
 impl<'a, RP, TP, PP> TransportBuilder<'a, RP, TP, PP>
 where
-    RP: TransportRulesProvider,
-    TP: TerrainProvider,
-    PP: PathPrioritizator,
+    RP: TransportRulesProvider + ?Sized,
+    TP: TerrainProvider + ?Sized,
+    PP: PathPrioritizator + ?Sized,
 {
     /// Create a new `TransportBuilder`.
     pub fn new(
@@ -46,31 +315,383 @@ where
             terrain_provider,
             path_prioritizator,
             stump_heap: BinaryHeap::new(),
+            missing_elevation_policy: MissingElevationPolicy::default(),
+            bounds: None,
+            boundary: None,
+            edge_taper: None,
+            construction_factors: BTreeMap::new(),
+            on_node_added: None,
+            on_path_added: None,
+            outcome: GrowthOutcome::default(),
+            counters: BuildCounters::default(),
+            stage_styles: BTreeMap::new(),
+            stage_from_terrain: None,
+            waypoints: None,
+            rules_error: Cell::new(None),
         }
     }
 
-    /// Add a path stump to the path network.
-    fn push_new_stump(
-        &mut self,
+    /// Create a new `TransportBuilder` which owns a random number generator created from `seed`.
+    ///
+    /// The network is reproducible from the seed, without passing the random number generator for each iteration.
+    pub fn seeded(
+        seed: u64,
+        rules_provider: &'a RP,
+        terrain_provider: &'a TP,
+        path_prioritizator: &'a PP,
+    ) -> SeededTransportBuilder<'a, RP, TP, PP> {
+        Self::new(rules_provider, terrain_provider, path_prioritizator).with_seed(seed)
+    }
+}
+
+impl<'a, RP, TP, PP, N> TransportBuilder<'a, RP, TP, PP, N>
+where
+    RP: TransportRulesProvider + ?Sized,
+    TP: TerrainProvider + ?Sized,
+    PP: PathPrioritizator + ?Sized,
+    N: TransportNodeTrait,
+{
+    /// Change the type of the nodes of the network to `M`, which is created from `TransportNode` by `From<TransportNode>`.
+    ///
+    /// This can be used to attach custom data to the nodes created by the builder.
+    /// The existing nodes are converted from their `TransportNode`, keeping the NodeIds, paths, and the other settings.
+    pub fn with_node_type<M>(self) -> TransportBuilder<'a, RP, TP, PP, M>
+    where
+        M: TransportNodeTrait,
+    {
+        TransportBuilder {
+            path_network: self
+                .path_network
+                .map_nodes(|_, node| M::from(*node.transport_node())),
+            rules_provider: self.rules_provider,
+            terrain_provider: self.terrain_provider,
+            path_prioritizator: self.path_prioritizator,
+            stump_heap: self.stump_heap,
+            missing_elevation_policy: self.missing_elevation_policy,
+            bounds: self.bounds,
+            boundary: self.boundary,
+            edge_taper: self.edge_taper,
+            construction_factors: self.construction_factors,
+            on_node_added: self.on_node_added,
+            on_path_added: self.on_path_added,
+            outcome: self.outcome,
+            counters: self.counters,
+            stage_styles: self.stage_styles,
+            stage_from_terrain: self.stage_from_terrain,
+            waypoints: self.waypoints,
+            rules_error: self.rules_error,
+        }
+    }
+
+    /// Replace the path network with an existing network (e.g. an authored network imported by `PathNetwork::from_detailed`).
+    ///
+    /// Paths are not extended from the nodes of the network until stumps are added by `seed_from_node`.
+    pub fn with_path_network(mut self, path_network: PathNetwork<N>) -> Self {
+        self.path_network = path_network;
+        self
+    }
+
+    /// Set the policy to handle sites where the terrain provider returns no elevation.
+    pub fn missing_elevation_policy(mut self, policy: MissingElevationPolicy) -> Self {
+        self.missing_elevation_policy = policy;
+        self
+    }
+
+    /// Clear the path network and the stumps, keeping the providers and the other settings.
+    ///
+    /// This can be used to generate another network with the same providers (e.g. from other origins).
+    pub fn reset(&mut self) {
+        self.path_network = PathNetwork::new();
+        self.stump_heap.clear();
+        self.construction_factors.clear();
+        self.outcome = GrowthOutcome::default();
+        self.counters = BuildCounters::default();
+        self.rules_error.set(None);
+    }
+
+    /// Set the callback which is called after a node is added to the network by the builder.
+    pub fn on_node_added<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(NodeId, &TransportNode) + 'a,
+    {
+        self.on_node_added = Some(Box::new(callback));
+        self
+    }
+
+    /// Set the callback which is called after a path is added to the network by the builder.
+    pub fn on_path_added<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut((NodeId, NodeId)) + 'a,
+    {
+        self.on_path_added = Some(Box::new(callback));
+        self
+    }
+
+    /// Set the rectangular bounds of the network.
+    ///
+    /// Paths are never extended outside the rectangle whose corners are `corner0` and `corner1`.
+    pub fn bounds(mut self, corner0: Site, corner1: Site) -> Self {
+        self.bounds = Some((
+            Site::new(corner0.x.min(corner1.x), corner0.y.min(corner1.y)),
+            Site::new(corner0.x.max(corner1.x), corner0.y.max(corner1.y)),
+        ));
+        self
+    }
+
+    /// Set the polygon boundary of the network.
+    ///
+    /// Paths are never extended outside the boundary.
+    /// This can be used together with `bounds` (the paths are extended only inside both of them).
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = Some(boundary);
+        self
+    }
+
+    /// Set the taper of the path length near the bounds.
+    ///
+    /// The normal length of paths extended from sites within `margin` from the bounds is scaled down
+    /// linearly, from 1.0 (at `margin`) to `min_scale` (at the bounds).
+    /// This has no effect if the bounds are not set by `bounds`.
+    pub fn edge_taper(mut self, margin: f64, min_scale: f64) -> Self {
+        self.edge_taper = Some((margin, min_scale));
+        self
+    }
+
+    /// Assign the stages of the origins added without stages from the slope of the terrain.
+    ///
+    /// The slope is measured along the first paths of the origin, over `path_normal_length`
+    /// of the rules of stage 0 at the origin. The steeper of the two directions is used.
+    pub fn stage_from_terrain(mut self, stage_from_terrain: StageFromTerrain) -> Self {
+        self.stage_from_terrain = Some(stage_from_terrain);
+        self
+    }
+
+    /// Get the stage of the origin from the slope of the terrain by `stage_from_terrain`.
+    fn origin_stage_from_terrain(&self, origin_site: Site, angle: Angle) -> Option<Stage> {
+        let stage_from_terrain = self.stage_from_terrain.as_ref()?;
+        let distance = self
+            .rules_provider
+            .get_rules(&origin_site, Stage::default(), &PathMetrics::default())?
+            .path_normal_length;
+        let terrain = self.terrain_with_policy(None);
+        let slope = [angle, angle.opposite()]
+            .into_iter()
+            .filter_map(|angle| {
+                terrain.slope_between(origin_site, origin_site.extend(angle, distance))
+            })
+            .map(f64::abs)
+            .fold(0.0, f64::max);
+        Some(stage_from_terrain.stage(slope))
+    }
+
+    /// Get the scale of the path length at the site by the edge taper.
+    fn edge_taper_scale(&self, site: &Site) -> f64 {
+        let ((min, max), (margin, min_scale)) =
+            if let (Some(bounds), Some(edge_taper)) = (self.bounds, self.edge_taper) {
+                (bounds, edge_taper)
+            } else {
+                return 1.0;
+            };
+        let distance = (site.x - min.x)
+            .min(max.x - site.x)
+            .min(site.y - min.y)
+            .min(max.y - site.y)
+            .max(0.0);
+        if margin <= 0.0 || distance >= margin {
+            return 1.0;
+        }
+        min_scale + (1.0 - min_scale) * distance / margin
+    }
+
+    /// Get the terrain provider which fills missing elevations by the policy.
+    fn terrain_with_policy(&self, last_elevation: Option<f64>) -> PolicyTerrainProvider<'_, TP> {
+        PolicyTerrainProvider {
+            terrain_provider: self.terrain_provider,
+            policy: self.missing_elevation_policy,
+            last_elevation,
+            bounds: self.bounds,
+            boundary: self.boundary.as_ref(),
+        }
+    }
+
+    /// Get the path prioritizator which adds the bias toward the waypoints.
+    fn prioritizator_with_waypoints(&self) -> WaypointPrioritizator<'_, PP> {
+        WaypointPrioritizator {
+            path_prioritizator: self.path_prioritizator,
+            waypoints: self.waypoints.as_ref(),
+        }
+    }
+
+    /// Set the waypoints which attract the growth of the paths (e.g. plazas or bridge sites).
+    ///
+    /// For each candidate direction of the stumps extended from within `attract_radius` of a waypoint,
+    /// `strength` multiplied by the cosine of the angle between the direction and the nearest waypoint is added to the priority.
+    /// Since the bias is added to the priority of `PathPrioritizator`, the directions toward the waypoint are
+    /// preferred in the comparison of the candidates, and the stumps heading to the waypoint are also extended earlier.
+    /// The paths rejected by `PathPrioritizator` are still rejected.
+    /// Unlike `grow_toward`, the paths are not guaranteed to reach the waypoints.
+    ///
+    /// If `waypoints` is empty, the waypoints are cleared.
+    pub fn with_waypoints(&mut self, waypoints: &[Site], attract_radius: f64, strength: f64) {
+        self.waypoints = if waypoints.is_empty() {
+            None
+        } else {
+            Some(Waypoints {
+                sites: waypoints.to_vec(),
+                attract_radius,
+                strength,
+            })
+        };
+    }
+
+    /// Create a path stump from the node.
+    fn create_stump<R>(
+        &self,
         node_start_id: NodeId,
         angle_expected_end: Angle,
         stage: Stage,
         metrics: PathMetrics,
-    ) -> Option<()> {
-        let node = self.path_network.get_node(node_start_id)?;
-
-        let rules = self.rules_provider.get_rules(&node.site, stage, &metrics)?;
+        rng: &mut R,
+    ) -> Option<Stump>
+    where
+        R: RandomF64Provider,
+    {
+        let node = self.get_node(node_start_id)?;
+        let rules = self.stump_rules(node, stage, &metrics)?;
 
-        let stump = Stump::create(
-            self.terrain_provider,
-            self.path_prioritizator,
+        Stump::create(
+            &self.terrain_with_policy(Some(node.elevation)),
+            &self.prioritizator_with_waypoints(),
             (node, node_start_id),
             angle_expected_end,
             stage,
             &rules,
             &metrics,
-        )?;
+            rng,
+        )
+    }
+
+    /// Get the rules to extend the path from the node.
+    ///
+    /// The error of the rules is recorded to be reported by `rules_error`.
+    /// Return `None` if the rules cannot construct paths.
+    fn stump_rules(
+        &self,
+        node: &TransportNode,
+        stage: Stage,
+        metrics: &PathMetrics,
+    ) -> Option<TransportRules> {
+        let mut rules = self.rules_provider.get_rules(&node.site, stage, metrics)?;
+        if let Err(error) = rules.validate() {
+            self.rules_error.set(Some(error));
+            if error.is_fatal() {
+                return None;
+            }
+        }
+        rules.path_normal_length *= self.edge_taper_scale(&node.site);
+        Some(rules)
+    }
+
+    /// Get the last error found in the rules returned by the rules provider.
+    ///
+    /// No stumps are created from the rules with fatal errors (see `RulesError::is_fatal`),
+    /// so this can be used to find why the growth stops.
+    pub fn rules_error(&self) -> Option<RulesError> {
+        self.rules_error.get()
+    }
+
+    /// Add a path stump to the path network.
+    fn push_new_stump<R>(
+        &mut self,
+        node_start_id: NodeId,
+        angle_expected_end: Angle,
+        stage: Stage,
+        metrics: PathMetrics,
+        rng: &mut R,
+    ) -> Option<()>
+    where
+        R: RandomF64Provider,
+    {
+        let stump = self.create_stump(node_start_id, angle_expected_end, stage, metrics, rng)?;
+
+        self.stump_heap.push(stump);
+
+        Some(())
+    }
+
+    /// Add a branch stump from the node, perpendicular to `straight_angle` (the direction of the parent path).
+    ///
+    /// If `branch_offset` of the rules of the parent path is positive, the branch starts from a new node
+    /// which is offset from the node by `branch_offset` along the normal of the parent path, on the side of the branch.
+    /// The new node is added only if the path to it satisfies the slope limit and `min_edge_length` of the rules
+    /// of the parent path, and the stump can be created from it.
+    #[allow(clippy::too_many_arguments)]
+    fn push_branch_stump<R>(
+        &mut self,
+        node_id: NodeId,
+        straight_angle: Angle,
+        clockwise: bool,
+        stage: Stage,
+        metrics: PathMetrics,
+        parent_rules: &TransportRules,
+        rng: &mut R,
+    ) -> Option<()>
+    where
+        R: RandomF64Provider,
+    {
+        let normal_angle = if clockwise {
+            straight_angle.right_clockwise()
+        } else {
+            straight_angle.right_counterclockwise()
+        };
+        let branch_offset = parent_rules.branch_rules.branch_offset;
+        if branch_offset <= 0.0 {
+            return self.push_new_stump(node_id, normal_angle, stage, metrics, rng);
+        }
+        // Paths shorter than the minimum length are not created.
+        if branch_offset < parent_rules.min_edge_length {
+            return None;
+        }
+
+        let node = *self.get_node(node_id)?;
+        let site = node.site.extend(normal_angle, branch_offset);
+        // the offset path must not cross other paths
+        if self
+            .path_network
+            .crossings_with_segment(node.site, site)
+            .iter()
+            .any(|((start, end), _)| *start != node_id && *end != node_id)
+        {
+            return None;
+        }
+        let elevation = self
+            .terrain_with_policy(Some(node.elevation))
+            .get_elevation(&site)?;
+        if !parent_rules
+            .path_slope_elevation_diff_limit
+            .check_slope((node.elevation, elevation), branch_offset)
+        {
+            return None;
+        }
+        let branch_node = TransportNode::new(site, elevation, stage, false);
+        let branch_node_id = self.path_network.add_node(branch_node.into());
+
+        let Some(stump) =
+            self.create_stump(branch_node_id, normal_angle, stage, metrics.clone(), rng)
+        else {
+            self.path_network.remove_node(branch_node_id);
+            return None;
+        };
 
+        self.record_added_node(branch_node_id, &branch_node);
+        self.path_network.set_parent(branch_node_id, node_id);
+        let factors = PathConstructionFactors {
+            stage,
+            metrics,
+            path_normal_length: parent_rules.path_normal_length,
+            growth_mode: parent_rules.growth_mode,
+        };
+        self.add_path_with_factors(node_id, branch_node_id, Some(factors));
         self.stump_heap.push(stump);
 
         Some(())
@@ -79,68 +700,405 @@ where
     /// Add an origin node to the path network.
     ///
     /// The path which is extended from `origin_site` by `angle_radian` (and the opposite path) will be the first stump_heap.
+    /// If `stage` is `None`, the stage is assigned by `stage_from_terrain` if it is set, otherwise stage 0 is used.
     pub fn add_origin(
         mut self,
         origin_site: Site,
         angle_radian: f64,
         stage: Option<Stage>,
     ) -> Option<Self> {
+        self.push_origin(origin_site, angle_radian, stage)?;
+        Some(self)
+    }
+
+    /// Add multiple origin nodes to the path network (e.g. several city centers growing toward each other).
+    ///
+    /// Each origin is the tuple of the site, the angle, and the stage, which are the same as the arguments of `add_origin`.
+    /// The origins which have no elevation are skipped.
+    /// Return `None` if no origins are added.
+    pub fn add_origins<I>(mut self, origins: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (Site, f64, Option<Stage>)>,
+    {
+        let added = origins
+            .into_iter()
+            .filter_map(|(origin_site, angle_radian, stage)| {
+                self.push_origin(origin_site, angle_radian, stage)
+            })
+            .count();
+        if added == 0 {
+            return None;
+        }
+        Some(self)
+    }
+
+    /// Add an origin node to the path network, and get which of the first stumps are added.
+    ///
+    /// This is the same as `add_origin`, but also returns the `SeedResult` to detect the origin
+    /// from which paths cannot be extended in either direction (e.g. the origin on a coastline).
+    pub fn add_origin_with_result(
+        mut self,
+        origin_site: Site,
+        angle_radian: f64,
+        stage: Option<Stage>,
+    ) -> Option<(Self, SeedResult)> {
+        let (_, seed_result) = self.push_origin(origin_site, angle_radian, stage)?;
+        Some((self, seed_result))
+    }
+
+    /// Add an origin node and its first stumps.
+    ///
+    /// Return `None` (without changing the builder) if the origin has no elevation.
+    fn push_origin(
+        &mut self,
+        origin_site: Site,
+        angle_radian: f64,
+        stage: Option<Stage>,
+    ) -> Option<(NodeId, SeedResult)> {
         let stage = if let Some(stage) = stage {
             stage
         } else {
-            Stage::from_num(0)
+            self.origin_stage_from_terrain(origin_site, Angle::new(angle_radian))
+                .unwrap_or_default()
         };
         let origin_node = TransportNode::new(
             origin_site,
-            self.terrain_provider.get_elevation(&origin_site)?,
+            self.terrain_with_policy(None).get_elevation(&origin_site)?,
             stage,
             false,
         );
-        let origin_node_id = self.path_network.add_node(origin_node);
+        let origin_node_id = self.add_node(origin_node);
         let origin_metrics = PathMetrics::default();
 
+        let forward = self
+            .push_new_stump(
+                origin_node_id,
+                Angle::new(angle_radian),
+                stage,
+                origin_metrics.incremented(false, false),
+                &mut MiddleRandom,
+            )
+            .is_some();
+        let backward = self
+            .push_new_stump(
+                origin_node_id,
+                Angle::new(angle_radian).opposite(),
+                stage,
+                origin_metrics.incremented(false, false),
+                &mut MiddleRandom,
+            )
+            .is_some();
+
+        Some((origin_node_id, SeedResult { forward, backward }))
+    }
+
+    /// Add a path stump extended from an existing node.
+    ///
+    /// The path which is extended from the node `node_id` by `angle_radian` will be added to stump_heap.
+    /// If `stage` is `None`, the stage of the node is used.
+    /// This can be used to reactivate the growth from any node in the network.
+    ///
+    /// Return `None` (without changing the builder) if the node does not exist in the network.
+    /// Note that the growth may not start if no path satisfying the rules can be extended in the direction.
+    pub fn seed_from_node<R>(
+        &mut self,
+        node_id: NodeId,
+        angle_radian: f64,
+        stage: Option<Stage>,
+        rng: &mut R,
+    ) -> Option<()>
+    where
+        R: RandomF64Provider,
+    {
+        let node_stage = self.get_node(node_id)?.stage;
+        let stage = if let Some(stage) = stage {
+            stage
+        } else {
+            node_stage
+        };
+
         self.push_new_stump(
-            origin_node_id,
+            node_id,
             Angle::new(angle_radian),
             stage,
-            origin_metrics.incremented(false, false),
-        );
-        self.push_new_stump(
-            origin_node_id,
-            Angle::new(angle_radian).opposite(),
-            stage,
-            origin_metrics.incremented(false, false),
+            PathMetrics::default().incremented(false, false),
+            rng,
         );
 
-        Some(self)
+        Some(())
     }
 
-    /// Iterate the path network `n` times.
-    pub fn iterate_n_times<R>(mut self, n: usize, rng: &mut R) -> Self
+    /// Re-seed stumps from the dead ends within `radius` of `site`, with the current rules of the providers.
+    ///
+    /// The dead ends are the nodes connected to only one path, except for the bridge nodes and the nodes
+    /// which already have pending stumps. Each stump is extended in the direction of the path to the dead end.
+    /// This can be used to regrow the network into the areas where the providers have changed (e.g. population grows).
+    ///
+    /// Return the number of the stumps added.
+    pub fn reactivate_near<R>(&mut self, site: Site, radius: f64, rng: &mut R) -> usize
     where
         R: RandomF64Provider,
     {
-        for _ in 0..n {
-            self = self.iterate::<R>(rng);
-        }
+        let frontier_nodes = self.frontier_nodes();
+        let dead_ends = self
+            .path_network
+            .nodes_around_site_iter(site, radius)
+            .copied()
+            .filter(|node_id| !frontier_nodes.contains(node_id))
+            .filter_map(|node_id| {
+                let node = self.get_node(node_id)?;
+                if node.is_bridge {
+                    return None;
+                }
+                let mut neighbors = self.path_network.neighbors_iter(node_id)?;
+                let (_, neighbor) = neighbors.next()?;
+                if neighbors.next().is_some() {
+                    return None;
+                }
+                Some((
+                    node_id,
+                    node.stage,
+                    neighbor.transport_node().site.get_angle(&node.site),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        dead_ends
+            .into_iter()
+            .filter_map(|(node_id, stage, angle)| {
+                self.push_new_stump(
+                    node_id,
+                    angle,
+                    stage,
+                    PathMetrics::default().incremented(false, false),
+                    rng,
+                )
+            })
+            .count()
+    }
+
+    /// Set the styles of the paths for each stage.
+    pub fn stage_styles(mut self, stage_styles: BTreeMap<Stage, StageStyle>) -> Self {
+        self.stage_styles = stage_styles;
+        self
+    }
+
+    /// Get the style of the paths of the stage.
+    pub fn stage_style(&self, stage: Stage) -> Option<&StageStyle> {
+        self.stage_styles.get(&stage)
+    }
+
+    /// Get the style of the path between `start` and `end`, determined by the stage of the path.
+    ///
+    /// Return `None` if the path does not exist or no style is set for the stage.
+    pub fn path_style(&self, start: NodeId, end: NodeId) -> Option<&StageStyle> {
+        self.path_network.edge_id(start, end)?;
+        let start = self.get_node(start)?;
+        let end = self.get_node(end)?;
+        self.stage_style(start.path_stage(end))
+    }
+
+    /// Get the factors of the rules referenced when the path between `start` and `end` was created.
+    ///
+    /// Return `None` if the path does not exist or was not created by this builder
+    /// (e.g. the paths of the network given by `with_path_network`).
+    pub fn edge_rules_summary(
+        &self,
+        start: NodeId,
+        end: NodeId,
+    ) -> Option<&PathConstructionFactors> {
+        let edge_id = self.path_network.edge_id(start, end)?;
+        self.construction_factors.get(&edge_id)
+    }
+
+    /// Evaluate the directions to extend the path from the node `node_id` by `angle_radian`, without changing the network.
+    ///
+    /// The directions are the same as those compared when a stump is created, with the stage of the node.
+    /// Each direction is reported with whether the path can be extended or why it is rejected.
+    /// This can be used to find out why the growth does not start from the node.
+    ///
+    /// Return an empty list if the node does not exist or the rules cannot construct paths.
+    pub fn probe_node<R>(
+        &self,
+        node_id: NodeId,
+        angle_radian: f64,
+        rng: &mut R,
+    ) -> Vec<(Angle, ProbeResult)>
+    where
+        R: RandomF64Provider,
+    {
+        let Some(node) = self.get_node(node_id) else {
+            return Vec::new();
+        };
+        let metrics = PathMetrics::default().incremented(false, false);
+        let Some(rules) = self.stump_rules(node, node.stage, &metrics) else {
+            return Vec::new();
+        };
+
+        Stump::probe(
+            &self.terrain_with_policy(Some(node.elevation)),
+            &self.prioritizator_with_waypoints(),
+            node,
+            Angle::new(angle_radian),
+            node.stage,
+            &rules,
+            rng,
+        )
+    }
+
+    /// Get the attributes of the node in the path network.
+    fn get_node(&self, node_id: NodeId) -> Option<&TransportNode> {
+        self.path_network
+            .get_node(node_id)
+            .map(TransportNodeTrait::transport_node)
+    }
+
+    /// Add a node to the path network and call the callback.
+    fn add_node(&mut self, node: TransportNode) -> NodeId {
+        let node_id = self.path_network.add_node(node.into());
+        self.record_added_node(node_id, &node);
+        node_id
+    }
+
+    /// Record the node added to the path network and call the callback.
+    fn record_added_node(&mut self, node_id: NodeId, node: &TransportNode) {
+        self.outcome.added_nodes.push(node_id);
+        self.counters.nodes_created += 1;
+        if let Some(on_node_added) = self.on_node_added.as_mut() {
+            on_node_added(node_id, node);
+        }
+    }
+
+    /// Add a path to the path network and record the factors of its construction.
+    fn add_path_with_factors(
+        &mut self,
+        start: NodeId,
+        end: NodeId,
+        factors: Option<PathConstructionFactors>,
+    ) {
+        if self.path_network.add_path(start, end).is_none() {
+            return;
+        }
+        self.outcome.added_paths.push((start, end));
+        self.counters.edges_added += 1;
+        if let Some(on_path_added) = self.on_path_added.as_mut() {
+            on_path_added((start, end));
+        }
+        if let (Some(edge_id), Some(factors)) = (self.path_network.edge_id(start, end), factors) {
+            self.construction_factors.insert(edge_id, factors);
+        }
+    }
+
+    /// Get the cumulative counts of the changes of the path network made by the builder since it is created or reset.
+    ///
+    /// The nodes and paths of the network given by `with_path_network` are not counted.
+    pub fn counters(&self) -> BuildCounters {
+        self.counters
+    }
+
+    /// Get the frontier nodes, which still have pending stumps to be extended.
+    ///
+    /// The nodes are returned as a `BTreeSet` rather than a `HashSet`,
+    /// so that they are iterated in the order of their NodeIds and the output is deterministic.
+    pub fn frontier_nodes(&self) -> BTreeSet<NodeId> {
+        self.stump_heap
+            .iter()
+            .map(|stump| stump.get_node_id())
+            .collect()
+    }
+
+    /// Iterate the path network `n` times.
+    pub fn iterate_n_times<R>(mut self, n: usize, rng: &mut R) -> Self
+    where
+        R: RandomF64Provider,
+    {
+        self.iterate_n_times_mut(n, rng);
         self
     }
 
+    /// Iterate the path network `n` times, without consuming the builder.
+    pub fn iterate_n_times_mut<R>(&mut self, n: usize, rng: &mut R)
+    where
+        R: RandomF64Provider,
+    {
+        for _ in 0..n {
+            self.iterate_mut(rng);
+        }
+    }
+
     /// Iterate network generation until there are no more stump_heap of new paths.
     pub fn iterate_as_possible<R>(mut self, rng: &mut R) -> Self
     where
         R: RandomF64Provider,
     {
-        while !self.stump_heap.is_empty() {
-            self = self.iterate::<R>(rng);
-        }
+        self.iterate_as_possible_mut(rng);
         self
     }
 
+    /// Iterate network generation until there are no more stump_heap of new paths, at most `max_iters` times.
+    ///
+    /// Return the builder and whether the generation is finished (`false` if it is stopped by the limit).
+    pub fn iterate_as_possible_capped<R>(mut self, max_iters: usize, rng: &mut R) -> (Self, bool)
+    where
+        R: RandomF64Provider,
+    {
+        for _ in 0..max_iters {
+            if self.step(rng).is_none() {
+                return (self, true);
+            }
+        }
+        let finished = self.stump_heap.is_empty();
+        (self, finished)
+    }
+
+    /// Iterate network generation until there are no more stump_heap of new paths, without consuming the builder.
+    pub fn iterate_as_possible_mut<R>(&mut self, rng: &mut R)
+    where
+        R: RandomF64Provider,
+    {
+        while self.step(rng).is_some() {}
+    }
+
+    /// Check if the node cannot have any more paths under the maximum degree of the rules.
+    fn is_saturated(&self, node_id: NodeId, rules: &TransportRules) -> bool {
+        let max_node_degree = if let Some(max_node_degree) = rules.max_node_degree {
+            max_node_degree
+        } else {
+            return false;
+        };
+        let degree = self
+            .path_network
+            .neighbors_iter(node_id)
+            .map_or(0, |neighbors| neighbors.count());
+        degree >= max_node_degree
+    }
+
+    /// Check if the path from the node `stump_node_id` to the next node is shorter than `min_edge_length` of the rules.
+    fn is_shorter_than_min_edge(
+        &self,
+        next_node_type: &NextNodeType,
+        stump_node_id: NodeId,
+        rules: &TransportRules,
+    ) -> Option<bool> {
+        let next_site = match next_node_type {
+            NextNodeType::New(node) | NextNodeType::Intersect(node, _) => Some(node.site),
+            NextNodeType::Existing(node_id) => self.get_node(*node_id).map(|node| node.site),
+            NextNodeType::None => None,
+        };
+        let stump_site = self.get_node(stump_node_id)?.site;
+        Some(next_site.is_some_and(|site| site.distance(&stump_site) < rules.min_edge_length))
+    }
+
     fn determine_growth_from_stump(&self, stump: &Stump) -> Option<GrowthTypes> {
-        let stump_node = self.path_network.get_node(stump.get_node_id())?;
+        let stump_node = self.get_node(stump.get_node_id())?;
+
+        // The stump node cannot have any more paths.
+        if self.is_saturated(stump.get_node_id(), stump.get_rules()) {
+            return None;
+        }
 
         // Find nodes around the line from the start site to the expected end site.
+        // Nodes which cannot have any more paths are excluded so that the path is not connected to them.
         let related_nodes = self
             .path_network
             .nodes_around_line_iter(
@@ -148,7 +1106,8 @@ where
                 stump.get_rules().path_extra_length_for_intersection,
             )
             .filter(|&node_id| *node_id != stump.get_node_id())
-            .filter_map(|node_id| Some((self.path_network.get_node(*node_id)?, *node_id)))
+            .filter(|&node_id| !self.is_saturated(*node_id, stump.get_rules()))
+            .filter_map(|node_id| Some((self.get_node(*node_id)?, *node_id)))
             .collect::<Vec<_>>();
 
         // Find paths touching the rectangle around the line.
@@ -159,8 +1118,8 @@ where
                 *node_id_start != stump.get_node_id() && *node_id_end != stump.get_node_id()
             })
             .filter_map(|(node_id_start, node_id_end)| {
-                let node_start = self.path_network.get_node(*node_id_start)?;
-                let node_end = self.path_network.get_node(*node_id_end)?;
+                let node_start = self.get_node(*node_id_start)?;
+                let node_end = self.get_node(*node_id_end)?;
                 Some(((node_start, *node_id_start), (node_end, *node_id_end)))
             })
             .collect::<Vec<_>>();
@@ -176,127 +1135,1972 @@ where
     where
         R: RandomF64Provider,
     {
-        let prior_stump = if let Some(stump) = self.stump_heap.pop() {
-            stump
-        } else {
-            return self;
-        };
+        self.iterate_mut(rng);
+        self
+    }
 
-        let growth = if let Some(growth) = self.determine_growth_from_stump(&prior_stump) {
-            growth
-        } else {
-            return self;
-        };
+    /// Iterate the path network to the next step, without consuming the builder.
+    pub fn iterate_mut<R>(&mut self, rng: &mut R)
+    where
+        R: RandomF64Provider,
+    {
+        self.step(rng);
+    }
 
-        self.apply_next_growth(
-            rng,
-            growth.next_node,
-            growth.bridge_node,
-            prior_stump.get_node_id(),
-            &prior_stump,
-        )
+    /// Iterate the path network to the next step and return the changes of the network.
+    ///
+    /// Return `None` if there are no more stumps to be extended.
+    pub fn step<R>(&mut self, rng: &mut R) -> Option<GrowthOutcome>
+    where
+        R: RandomF64Provider,
+    {
+        let mut prior_stump = self.stump_heap.pop()?;
+        self.outcome = GrowthOutcome::default();
+
+        // Stumps in `GrowthMode::StraightUntilBlocked` are extended continuously in this iteration.
+        while let Some(growth) = self.determine_growth_from_stump(&prior_stump) {
+            let continued_stump = self.apply_next_growth(
+                rng,
+                growth.next_node,
+                growth.bridge_node,
+                prior_stump.get_node_id(),
+                &prior_stump,
+            );
+
+            if let Some(stump) = continued_stump {
+                prior_stump = stump;
+            } else {
+                break;
+            }
+        }
+
+        Some(std::mem::take(&mut self.outcome))
     }
 
+    /// Apply the growth to the path network.
+    ///
+    /// If the path is to be extended continuously in the same iteration, the next stump is returned.
     fn apply_next_growth<R>(
-        mut self,
+        &mut self,
         rng: &mut R,
         next_node_type: NextNodeType,
         bridge_node_type: BridgeNodeType,
         stump_node_id: NodeId,
         stump: &Stump,
-    ) -> Self
+    ) -> Option<Stump>
     where
         R: RandomF64Provider,
     {
-        if let BridgeNodeType::Middle(bridge_node) = bridge_node_type {
-            let bridge_node_id = self.path_network.add_node(bridge_node);
-            self.path_network.add_path(stump_node_id, bridge_node_id);
+        let factors = PathConstructionFactors {
+            stage: stump.get_stage(),
+            metrics: stump.get_metrics().clone(),
+            path_normal_length: stump.get_rules().path_normal_length,
+            growth_mode: stump.get_rules().growth_mode,
+        };
 
-            return self.apply_next_growth(
-                rng,
-                next_node_type,
-                BridgeNodeType::None,
-                bridge_node_id,
-                stump,
-            );
+        // Paths shorter than the minimum length are not created.
+        if self.is_shorter_than_min_edge(&next_node_type, stump_node_id, stump.get_rules())? {
+            return None;
+        }
+
+        let (start_node_id, node_id) =
+            self.connect_growth(&next_node_type, bridge_node_type, stump_node_id, factors)?;
+
+        if let NextNodeType::New(node_next) = next_node_type {
+            let start_site = self.get_node(start_node_id)?.site;
+            let straight_angle = start_site.get_angle(&node_next.site);
+            let (next_angle, next_metrics) =
+                if let Some(switchback_angle) = stump.get_switchback_angle() {
+                    // Return to the original direction after a switchback segment.
+                    // If it is still too steep, the next segment will turn to the opposite side.
+                    (
+                        switchback_angle,
+                        stump
+                            .get_metrics()
+                            .incremented(false, false)
+                            .switchback_incremented(),
+                    )
+                } else {
+                    (
+                        straight_angle,
+                        stump.get_metrics().incremented(false, false),
+                    )
+                };
+
+            // The random value is drawn only if the path can be terminated,
+            // so that the rules without termination generate the same network as before.
+            let termination_probability = stump.get_rules().branch_rules.termination_probability;
+            let terminates =
+                termination_probability > 0.0 && draw_random(rng) < termination_probability;
+
+            if stump.get_rules().growth_mode == GrowthMode::StraightUntilBlocked {
+                if terminates {
+                    return None;
+                }
+                // Extend the path without branches in the same iteration.
+                let next_stump =
+                    self.create_stump(node_id, next_angle, stump.get_stage(), next_metrics, rng);
+                return next_stump;
+            }
+
+            if !terminates {
+                self.push_new_stump(node_id, next_angle, stump.get_stage(), next_metrics, rng);
+            }
+
+            let clockwise_branch = !stump.get_rules().forbid_branch
+                && draw_random(rng) < stump.get_rules().branch_rules.branch_density;
+            if clockwise_branch {
+                let clockwise_staging =
+                    draw_random(rng) < stump.get_rules().branch_rules.staging_probability;
+                let next_stage = if clockwise_staging {
+                    stump.get_stage().incremented()
+                } else {
+                    stump.get_stage()
+                };
+                self.push_branch_stump(
+                    node_id,
+                    straight_angle,
+                    true,
+                    next_stage,
+                    stump.get_metrics().incremented(clockwise_staging, true),
+                    stump.get_rules(),
+                    rng,
+                );
+            }
+
+            let counterclockwise_branch = !stump.get_rules().forbid_branch
+                && draw_random(rng) < stump.get_rules().branch_rules.branch_density;
+            if counterclockwise_branch {
+                let counterclockwise_staging =
+                    draw_random(rng) < stump.get_rules().branch_rules.staging_probability;
+                let next_stage = if counterclockwise_staging {
+                    stump.get_stage().incremented()
+                } else {
+                    stump.get_stage()
+                };
+                self.push_branch_stump(
+                    node_id,
+                    straight_angle,
+                    false,
+                    next_stage,
+                    stump
+                        .get_metrics()
+                        .incremented(counterclockwise_staging, true),
+                    stump.get_rules(),
+                    rng,
+                );
+            }
         }
 
-        let start_site = if let Some(node) = self.path_network.get_node(stump_node_id) {
-            node.site
+        None
+    }
+
+    /// Add the nodes and paths of the growth from the node `stump_node_id` to the path network.
+    ///
+    /// Return the node from which the last path starts (the bridge node if it is created) and the node reached by the growth,
+    /// or `None` if the growth reaches no node.
+    fn connect_growth(
+        &mut self,
+        next_node_type: &NextNodeType,
+        bridge_node_type: BridgeNodeType,
+        stump_node_id: NodeId,
+        factors: PathConstructionFactors,
+    ) -> Option<(NodeId, NodeId)> {
+        let stump_node_id = if let BridgeNodeType::Middle(bridge_node) = bridge_node_type {
+            let bridge_node_id = self.add_node(bridge_node);
+            self.path_network.set_parent(bridge_node_id, stump_node_id);
+            self.add_path_with_factors(stump_node_id, bridge_node_id, Some(factors.clone()));
+            bridge_node_id
         } else {
-            return self;
+            stump_node_id
         };
 
-        match next_node_type {
+        if !self.path_network.contains(stump_node_id) {
+            return None;
+        }
+
+        let node_id = match *next_node_type {
             NextNodeType::None => {
-                return self;
+                return None;
             }
             NextNodeType::Existing(node_id) => {
-                self.path_network.add_path(stump_node_id, node_id);
+                self.add_path_with_factors(stump_node_id, node_id, Some(factors));
+                node_id
             }
             NextNodeType::Intersect(node_next, encount_path) => {
-                let next_node_id = self.path_network.add_node(node_next);
-                self.path_network
-                    .remove_path(encount_path.0, encount_path.1);
-                self.path_network.add_path(stump_node_id, next_node_id);
-                self.path_network.add_path(next_node_id, encount_path.0);
-                self.path_network.add_path(next_node_id, encount_path.1);
+                let next_node_id = self.add_node(node_next);
+                self.path_network.set_parent(next_node_id, stump_node_id);
+                // The encountered path is split into two paths, which are given new EdgeIds
+                // and inherit the factors of the encountered path.
+                let encount_factors = self
+                    .path_network
+                    .edge_id(encount_path.0, encount_path.1)
+                    .and_then(|edge_id| self.construction_factors.remove(&edge_id));
+                if self
+                    .path_network
+                    .remove_path(encount_path.0, encount_path.1)
+                    .is_some()
+                {
+                    self.outcome.removed_paths.push(encount_path);
+                    self.counters.edges_removed += 1;
+                }
+                self.add_path_with_factors(stump_node_id, next_node_id, Some(factors));
+                self.add_path_with_factors(next_node_id, encount_path.0, encount_factors.clone());
+                self.add_path_with_factors(next_node_id, encount_path.1, encount_factors);
+                next_node_id
             }
             NextNodeType::New(node_next) => {
-                let node_id = self.path_network.add_node(node_next);
-                self.path_network.add_path(stump_node_id, node_id);
+                let node_id = self.add_node(node_next);
+                self.path_network.set_parent(node_id, stump_node_id);
+                self.add_path_with_factors(stump_node_id, node_id, Some(factors));
+                node_id
+            }
+        };
 
-                let straight_angle = start_site.get_angle(&node_next.site);
-                self.push_new_stump(
-                    node_id,
-                    straight_angle,
-                    stump.get_stage(),
-                    stump.get_metrics().incremented(false, false),
-                );
-                let clockwise_branch =
-                    rng.gen_f64() < stump.get_rules().branch_rules.branch_density;
-                if clockwise_branch {
-                    let clockwise_staging =
-                        rng.gen_f64() < stump.get_rules().branch_rules.staging_probability;
-                    let next_stage = if clockwise_staging {
-                        stump.get_stage().incremented()
-                    } else {
-                        stump.get_stage()
-                    };
-                    self.push_new_stump(
-                        node_id,
-                        straight_angle.right_clockwise(),
-                        next_stage,
-                        stump.get_metrics().incremented(clockwise_staging, true),
-                    );
+        Some((stump_node_id, node_id))
+    }
+
+    /// Grow a path from the node `start` toward the site `target`, and connect it to a node at `target`.
+    ///
+    /// Each segment is directed to `target` and curved by the terrain within the rules, like the other paths.
+    /// If the path meets an existing node or path, it is connected there and continues from the connected node.
+    /// No branches are created, and no stumps are added to be extended later.
+    /// When the path reaches within `path_normal_length` of `target`, the last segment is directed exactly to `target`
+    /// and checked in the same way as the other segments. If an existing node is within `path_extra_length_for_intersection`
+    /// of `target`, the path is connected to it instead of creating a new node.
+    /// If `stage` is `None`, the stage of the node `start` is used.
+    ///
+    /// Return the NodeId of the node at `target`, or `None` if the path is blocked or stops approaching `target`.
+    /// The paths created until then remain in the network.
+    pub fn grow_toward<R>(
+        &mut self,
+        start: NodeId,
+        target: Site,
+        stage: Option<Stage>,
+        rng: &mut R,
+    ) -> Option<NodeId>
+    where
+        R: RandomF64Provider,
+    {
+        let stage = if let Some(stage) = stage {
+            stage
+        } else {
+            self.get_node(start)?.stage
+        };
+        let mut metrics = PathMetrics::default().incremented(false, false);
+        let mut current = start;
+        loop {
+            let node = *self.get_node(current)?;
+            let distance = node.site.distance(&target);
+            let rules = self.stump_rules(&node, stage, &metrics)?;
+            let reaches_target = distance <= rules.path_normal_length;
+            // the existing node at the target to be connected by the last segment
+            let mut target_node = None;
+            let stump = if reaches_target {
+                target_node = self
+                    .path_network
+                    .nodes_around_site_iter(target, rules.path_extra_length_for_intersection)
+                    .filter(|node_id| **node_id != current)
+                    .filter(|node_id| !self.is_saturated(**node_id, &rules))
+                    .filter_map(|node_id| Some((*node_id, *self.get_node(*node_id)?)))
+                    .filter(|(_, node)| !node.is_bridge)
+                    .min_by(|(_, a), (_, b)| {
+                        a.site
+                            .distance_2(&target)
+                            .total_cmp(&b.site.distance_2(&target))
+                    });
+                // the last segment is pinned to the target
+                let node_expected_end = if let Some((_, target_node)) = target_node {
+                    TransportNode::new(target_node.site, target_node.elevation, stage, false)
+                } else {
+                    let elevation = self
+                        .terrain_with_policy(Some(node.elevation))
+                        .get_elevation(&target)?;
+                    TransportNode::new(target, elevation, stage, false)
+                };
+                Stump::new(
+                    current,
+                    node_expected_end,
+                    rules,
+                    metrics.clone(),
+                    0.0,
+                    false,
+                )
+            } else {
+                self.create_stump(
+                    current,
+                    node.site.get_angle(&target),
+                    stage,
+                    metrics.clone(),
+                    rng,
+                )?
+            };
+            let mut growth = self.determine_growth_from_stump(&stump)?;
+            if let (Some((target_node_id, _)), NextNodeType::New(_)) =
+                (target_node, &growth.next_node)
+            {
+                // the path reaches the site of the existing node without crossing other paths
+                growth.next_node = NextNodeType::Existing(target_node_id);
+            }
+            if self.is_shorter_than_min_edge(&growth.next_node, current, stump.get_rules())? {
+                return None;
+            }
+            let factors = PathConstructionFactors {
+                stage,
+                metrics: metrics.clone(),
+                path_normal_length: stump.get_rules().path_normal_length,
+                growth_mode: stump.get_rules().growth_mode,
+            };
+            let (_, next) =
+                self.connect_growth(&growth.next_node, growth.bridge_node, current, factors)?;
+
+            let next_site = self.get_node(next)?.site;
+            if reaches_target {
+                match growth.next_node {
+                    NextNodeType::New(_) => return Some(next),
+                    NextNodeType::Existing(node_id)
+                        if target_node
+                            .is_some_and(|(target_node_id, _)| target_node_id == node_id) =>
+                    {
+                        return Some(next)
+                    }
+                    // the path is connected to a node or a path before the target
+                    _ => {}
                 }
+            }
 
-                let counterclockwise_branch =
-                    rng.gen_f64() < stump.get_rules().branch_rules.branch_density;
-                if counterclockwise_branch {
-                    let counterclockwise_staging =
-                        rng.gen_f64() < stump.get_rules().branch_rules.staging_probability;
-                    let next_stage = if counterclockwise_staging {
-                        stump.get_stage().incremented()
-                    } else {
-                        stump.get_stage()
-                    };
-                    self.push_new_stump(
-                        node_id,
-                        straight_angle.right_counterclockwise(),
-                        next_stage,
-                        stump
-                            .get_metrics()
-                            .incremented(counterclockwise_staging, true),
-                    );
+            // stop if the path does not approach the target (e.g. it is connected to a node behind)
+            if next_site.distance(&target) >= distance {
+                return None;
+            }
+            current = next;
+            metrics = metrics.incremented(false, false);
+        }
+    }
+
+    /// Generate path networks in rectangular tiles in parallel and merge them into one network.
+    ///
+    /// Each tile is a pair of corner sites. For each tile, a new network is generated
+    /// from the `origins` (site, angle in radian, and stage) inside the tile until no more paths can be extended.
+    /// The paths are never extended outside the tile (and the boundary set by `with_boundary`),
+    /// and the network and stumps of this builder are not used.
+    /// The settings of this builder (e.g. `edge_taper`, `stage_from_terrain`, and the waypoints) are used for each tile,
+    /// but the hooks (`on_node_added` and `on_path_added`) are not called for the nodes and paths of the tiles.
+    /// The random provider of the `i`-th tile is created by `create_rng(rng_seed.wrapping_add(i))`.
+    ///
+    /// The tiles are generated by at most `std::thread::available_parallelism()` threads.
+    ///
+    /// The networks are merged in the order of `tiles`. Each node of a tile is snapped to
+    /// the nearest node of the preceding tiles within `snap_distance`, so that paths meeting at borders are connected.
+    ///
+    /// The result is deterministic: it depends only on the arguments (if `create_rng` is deterministic),
+    /// not on the number of threads or the order in which the tiles finish.
+    ///
+    /// Note that the result differs from the network generated without tiles:
+    ///  - paths reaching a border are cut there and are connected only if the nodes of both sides are within `snap_distance`.
+    ///  - paths of overlapping tiles may cross each other without intersections.
+    ///  - snapped paths are not checked for crossings with other paths.
+    pub fn generate_tiled<R, F>(
+        &self,
+        tiles: &[(Site, Site)],
+        origins: &[(Site, f64, Option<Stage>)],
+        rng_seed: u64,
+        create_rng: F,
+        snap_distance: f64,
+    ) -> PathNetwork<N>
+    where
+        RP: Sync,
+        TP: Sync,
+        PP: Sync,
+        R: RandomF64Provider,
+        F: Fn(u64) -> R + Sync,
+    {
+        let (rules_provider, terrain_provider, path_prioritizator) = (
+            self.rules_provider,
+            self.terrain_provider,
+            self.path_prioritizator,
+        );
+        let (missing_elevation_policy, edge_taper) =
+            (self.missing_elevation_policy, self.edge_taper);
+        let (boundary, waypoints, stage_from_terrain) =
+            (&self.boundary, &self.waypoints, &self.stage_from_terrain);
+        let generate_tile = |i: usize, tile: &(Site, Site)| {
+            let mut builder =
+                TransportBuilder::new(rules_provider, terrain_provider, path_prioritizator)
+                    .missing_elevation_policy(missing_elevation_policy)
+                    .bounds(tile.0, tile.1);
+            builder.boundary = boundary.clone();
+            builder.waypoints = waypoints.clone();
+            builder.edge_taper = edge_taper;
+            builder.stage_from_terrain = stage_from_terrain.clone();
+            origins.iter().for_each(|(site, angle_radian, stage)| {
+                builder.push_origin(*site, *angle_radian, *stage);
+            });
+            builder
+                .iterate_as_possible(&mut create_rng(rng_seed.wrapping_add(i as u64)))
+                .path_network
+        };
+
+        // The tiles are generated by a bounded number of workers, each of which takes the next tile in turn.
+        let worker_count = std::thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .min(tiles.len());
+        let next_tile = AtomicUsize::new(0);
+        let mut tile_networks = std::thread::scope(|scope| {
+            let workers = (0..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut networks = Vec::new();
+                        loop {
+                            let i = next_tile.fetch_add(1, AtomicOrdering::Relaxed);
+                            let Some(tile) = tiles.get(i) else {
+                                break;
+                            };
+                            networks.push((i, generate_tile(i, tile)));
+                        }
+                        networks
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("tile generation panicked"))
+                .collect::<Vec<_>>()
+        });
+        tile_networks.sort_by_key(|(i, _)| *i);
+
+        let mut path_network = PathNetwork::new();
+        for (_, tile_network) in tile_networks.iter() {
+            let node_ids = path_network.append(tile_network);
+            let tile_node_ids = node_ids.iter().copied().collect::<BTreeSet<_>>();
+            for node_id in node_ids {
+                let site = if let Some(node) = path_network.get_node(node_id) {
+                    node.site
+                } else {
+                    continue;
+                };
+                // Snap to the nearest node of the preceding tiles.
+                let nearest = path_network
+                    .nodes_around_site_iter(site, snap_distance)
+                    .filter(|other_id| !tile_node_ids.contains(other_id))
+                    .filter_map(|other_id| {
+                        let distance = path_network.get_node(*other_id)?.site.distance(&site);
+                        Some((*other_id, distance))
+                    })
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                if let Some((nearest_id, _)) = nearest {
+                    path_network.snap_nodes(node_id, nearest_id);
                 }
             }
         }
+        path_network.map_nodes(|_, node| N::from(*node))
+    }
+
+    /// Get the optimized copy of the path network (see `PathNetwork::reconstruct`) and the builder itself.
+    pub fn snapshot(self) -> (Option<PathNetwork<N>>, Self) {
+        (self.path_network.clone().reconstruct(), self)
+    }
+
+    /// Make the builder own a random number generator created from `seed`.
+    ///
+    /// This can be used to set up the builder (e.g. bounds and hooks) before seeding.
+    pub fn with_seed(self, seed: u64) -> SeededTransportBuilder<'a, RP, TP, PP, N> {
+        SeededTransportBuilder {
+            builder: self,
+            rng: SeededRandom::new(seed),
+        }
+    }
+}
+
+/// `TransportBuilder` which owns a random number generator.
+///
+/// The iterations use the owned generator instead of the one passed by the caller.
+/// Create with `TransportBuilder::seeded` or `TransportBuilder::with_seed`.
+pub struct SeededTransportBuilder<'a, RP, TP, PP, N = TransportNode>
+where
+    RP: TransportRulesProvider + ?Sized,
+    TP: TerrainProvider + ?Sized,
+    PP: PathPrioritizator + ?Sized,
+    N: TransportNodeTrait,
+{
+    builder: TransportBuilder<'a, RP, TP, PP, N>,
+    rng: SeededRandom,
+}
+
+impl<'a, RP, TP, PP, N> SeededTransportBuilder<'a, RP, TP, PP, N>
+where
+    RP: TransportRulesProvider + ?Sized,
+    TP: TerrainProvider + ?Sized,
+    PP: PathPrioritizator + ?Sized,
+    N: TransportNodeTrait,
+{
+    /// Add an origin node to the path network. See `TransportBuilder::add_origin`.
+    pub fn add_origin(
+        mut self,
+        origin_site: Site,
+        angle_radian: f64,
+        stage: Option<Stage>,
+    ) -> Option<Self> {
+        self.builder = self.builder.add_origin(origin_site, angle_radian, stage)?;
+        Some(self)
+    }
 
+    /// Iterate the path network to the next step.
+    pub fn iterate(mut self) -> Self {
+        self.builder.iterate_mut(&mut self.rng);
         self
     }
 
-    pub fn snapshot(self) -> (Option<PathNetwork<TransportNode>>, Self) {
-        (self.path_network.clone().reconstruct(), self)
+    /// Iterate the path network `n` times.
+    pub fn iterate_n_times(mut self, n: usize) -> Self {
+        self.builder.iterate_n_times_mut(n, &mut self.rng);
+        self
+    }
+
+    /// Iterate network generation until there are no more stump_heap of new paths.
+    pub fn iterate_as_possible(mut self) -> Self {
+        self.builder.iterate_as_possible_mut(&mut self.rng);
+        self
+    }
+
+    /// Iterate the path network to the next step and return the changes of the network.
+    ///
+    /// Return `None` if there are no more stumps to be extended.
+    pub fn step(&mut self) -> Option<GrowthOutcome> {
+        self.builder.step(&mut self.rng)
+    }
+
+    /// Get the builder.
+    pub fn builder(&self) -> &TransportBuilder<'a, RP, TP, PP, N> {
+        &self.builder
+    }
+
+    /// Get the mutable builder (e.g. to add origins or reactivate the growth).
+    pub fn builder_mut(&mut self) -> &mut TransportBuilder<'a, RP, TP, PP, N> {
+        &mut self.builder
+    }
+
+    /// Get the owned random number generator (e.g. to pass it to the methods of the builder).
+    pub fn rng_mut(&mut self) -> &mut SeededRandom {
+        &mut self.rng
+    }
+
+    /// Get the builder, dropping the owned random number generator.
+    pub fn into_builder(self) -> TransportBuilder<'a, RP, TP, PP, N> {
+        self.builder
+    }
+
+    /// Get the optimized copy of the path network (see `PathNetwork::reconstruct`) and the builder itself.
+    pub fn snapshot(self) -> (Option<PathNetwork<N>>, Self) {
+        (self.builder.path_network.clone().reconstruct(), self)
+    }
+}
+
+impl<'a> DynTransportBuilder<'a> {
+    /// Create a new `TransportBuilder` from the providers as trait objects.
+    pub fn new_dyn(
+        rules_provider: &'a dyn TransportRulesProvider,
+        terrain_provider: &'a dyn TerrainProvider,
+        path_prioritizator: &'a dyn PathPrioritizator,
+    ) -> Self {
+        Self::new(rules_provider, terrain_provider, path_prioritizator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::params::rules::{
+        branch::BranchRules,
+        bridge::BridgeRules,
+        direction::{GridBias, PathDirectionRules},
+        ElevationDiffLimit,
+    };
+
+    use super::*;
+
+    struct FlatProvider {
+        rules: TransportRules,
+        /// the terrain exists only within this distance from the origin
+        bound: f64,
+    }
+
+    impl TransportRulesProvider for FlatProvider {
+        fn get_rules(&self, _: &Site, _: Stage, _: &PathMetrics) -> Option<TransportRules> {
+            Some(self.rules.clone())
+        }
+    }
+
+    impl TerrainProvider for FlatProvider {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            if site.distance(&Site::default()) <= self.bound {
+                Some(0.0)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl PathPrioritizator for FlatProvider {
+        fn prioritize(&self, _: PathPrioritizationFactors) -> Option<f64> {
+            Some(0.0)
+        }
+    }
+
+    struct ConstantRandom;
+
+    impl RandomF64Provider for ConstantRandom {
+        fn gen_f64(&mut self) -> f64 {
+            0.5
+        }
+    }
+
+    #[test]
+    fn test_max_node_degree() {
+        let grow_into_junction = |rules: TransportRules| {
+            let provider = FlatProvider {
+                rules,
+                bound: f64::MAX,
+            };
+            let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+
+            // a junction which has 4 paths
+            let node =
+                |x: f64, y: f64| TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false);
+            let junction = builder.path_network.add_node(node(0.2, -1.0));
+            for (x, y) in [(1.2, -1.0), (0.2, -2.0), (1.0, -1.8), (1.2, -0.5)] {
+                let arm = builder.path_network.add_node(node(x, y));
+                builder.path_network.add_path(junction, arm);
+            }
+            let start = builder.path_network.add_node(node(0.0, 0.0));
+
+            builder
+                .seed_from_node(start, 0.0, None, &mut ConstantRandom)
+                .unwrap();
+            let builder = builder.iterate(&mut ConstantRandom);
+            let neighbors = builder
+                .path_network
+                .neighbors_iter(start)
+                .unwrap()
+                .map(|(node_id, _)| node_id)
+                .collect::<Vec<_>>();
+            assert_eq!(neighbors.len(), 1);
+            neighbors[0] == junction
+        };
+
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_extra_length_for_intersection(0.25);
+
+        // connected to the junction
+        assert!(grow_into_junction(rules.clone()));
+
+        // routed around the saturated junction
+        assert!(!grow_into_junction(rules.max_node_degree(4)));
+    }
+
+    #[test]
+    fn test_counters() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let node =
+            |x: f64, y: f64| TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false);
+        let west = builder.path_network.add_node(node(-1.0, -0.8));
+        let east = builder.path_network.add_node(node(1.0, -0.8));
+        builder.path_network.add_path(west, east);
+        let start = builder.path_network.add_node(node(0.0, 0.0));
+
+        // the path crosses the existing path and splits it
+        builder
+            .seed_from_node(start, 0.0, None, &mut ConstantRandom)
+            .unwrap();
+        builder.iterate_mut(&mut ConstantRandom);
+        assert_eq!(
+            builder.counters(),
+            BuildCounters {
+                nodes_created: 1,
+                edges_added: 3,
+                edges_removed: 1,
+            }
+        );
+        assert_eq!(builder.path_network.nodes_iter().count(), 4);
+
+        builder.reset();
+        assert_eq!(builder.counters(), BuildCounters::default());
+    }
+
+    #[test]
+    fn test_with_node_type() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct DistrictNode {
+            node: TransportNode,
+            district: usize,
+        }
+
+        impl From<TransportNode> for DistrictNode {
+            fn from(node: TransportNode) -> Self {
+                let district = if node.site.x < 0.0 { 0 } else { 1 };
+                Self { node, district }
+            }
+        }
+
+        impl From<DistrictNode> for Site {
+            fn from(node: DistrictNode) -> Self {
+                node.node.site
+            }
+        }
+
+        impl TransportNodeTrait for DistrictNode {
+            fn transport_node(&self) -> &TransportNode {
+                &self.node
+            }
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 3.0,
+        };
+        let (network, _) = TransportBuilder::new(&provider, &provider, &provider)
+            .with_node_type::<DistrictNode>()
+            .add_origin(Site::default(), std::f64::consts::PI * 0.5, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom)
+            .snapshot();
+        let network = network.unwrap();
+
+        // the nodes created by the growth carry the payload
+        assert!(network.nodes_iter().count() > 3);
+        assert!(network.nodes_iter().any(|(_, node)| node.district == 0));
+        assert!(network.nodes_iter().any(|(_, node)| node.district == 1));
+        network.nodes_iter().for_each(|(_, node)| {
+            assert_eq!(node.district, DistrictNode::from(node.node).district);
+        });
+    }
+
+    #[test]
+    fn test_seed_from_node() {
+        struct ZeroRandom;
+
+        impl RandomF64Provider for ZeroRandom {
+            fn gen_f64(&mut self) -> f64 {
+                0.0
+            }
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .path_length_jitter(0.5)
+                .forbid_branch(true),
+            bound: f64::MAX,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_n_times(4, &mut ConstantRandom);
+
+        // a node in the middle of the network, connected to two paths
+        let (middle, middle_site) = builder
+            .path_network
+            .nodes_iter()
+            .find(|(node_id, _)| {
+                builder
+                    .path_network
+                    .neighbors_iter(*node_id)
+                    .is_some_and(|neighbors| neighbors.count() == 2)
+            })
+            .map(|(node_id, node)| (node_id, node.site))
+            .unwrap();
+
+        // the builder is not changed by the unknown node
+        let removed = builder.path_network.add_node(TransportNode::default());
+        builder.path_network.remove_node(removed);
+        let frontier = builder.frontier_nodes();
+        assert_eq!(
+            builder.seed_from_node(removed, 0.0, None, &mut ZeroRandom),
+            None
+        );
+        assert_eq!(builder.frontier_nodes(), frontier);
+
+        builder
+            .seed_from_node(middle, std::f64::consts::PI * 0.5, None, &mut ZeroRandom)
+            .unwrap();
+        assert!(builder.frontier_nodes().contains(&middle));
+
+        // grow the seeded path only
+        builder
+            .stump_heap
+            .retain(|stump| stump.get_node_id() == middle);
+        let outcome = builder.step(&mut ConstantRandom).unwrap();
+        assert_eq!(outcome.added_paths.len(), 1);
+        let added = builder
+            .path_network
+            .get_node(outcome.added_nodes[0])
+            .unwrap();
+        assert!(builder
+            .path_network
+            .has_path(middle, outcome.added_nodes[0]));
+        // the length is varied by the random provider passed to `seed_from_node`
+        assert!((added.site.distance(&middle_site) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_straight_until_blocked() {
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .growth_mode(GrowthMode::StraightUntilBlocked),
+            bound: 5.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let origin = builder.path_network.add_node(TransportNode::new(
+            Site::default(),
+            0.0,
+            Stage::default(),
+            false,
+        ));
+        builder
+            .seed_from_node(
+                origin,
+                std::f64::consts::PI * 0.5,
+                None,
+                &mut ConstantRandom,
+            )
+            .unwrap();
+        assert_eq!(builder.frontier_nodes(), BTreeSet::from([origin]));
+        let builder = builder.iterate(&mut ConstantRandom);
+
+        // all segments are created in one iteration
+        assert_eq!(builder.path_network.nodes_iter().count(), 6);
+        assert!(builder.frontier_nodes().is_empty());
+    }
+
+    #[test]
+    fn test_generate_tiled() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let builder = TransportBuilder::new(&provider, &provider, &provider);
+        let tiles = [
+            (Site::new(-4.0, -1.0), Site::new(0.0, 1.0)),
+            (Site::new(4.0, 1.0), Site::new(0.0, -1.0)),
+        ];
+        let origins = [
+            (Site::new(-2.25, 0.0), std::f64::consts::PI * 0.5, None),
+            (Site::new(2.25, 0.0), std::f64::consts::PI * 0.5, None),
+        ];
+        let generate = || builder.generate_tiled(&tiles, &origins, 0, |_| ConstantRandom, 0.75);
+
+        let network = generate();
+        // 4 nodes in each tile, and the nodes at -0.25 and 0.25 are snapped
+        assert_eq!(network.nodes_iter().count(), 7);
+        let start = network.search_nearest_node(Site::new(-4.0, 0.0)).unwrap();
+        let end = network.search_nearest_node(Site::new(4.0, 0.0)).unwrap();
+        assert!(network
+            .reachable_within(start, f64::MAX)
+            .iter()
+            .any(|(node_id, _)| *node_id == end));
+
+        // deterministic
+        let sites = |network: &PathNetwork<TransportNode>| {
+            network
+                .nodes_iter()
+                .map(|(_, node)| node.site)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sites(&generate()), sites(&network));
+
+        // the settings of the builder are applied to each tile
+        let tapered = builder.edge_taper(2.0, 0.5).generate_tiled(
+            &tiles,
+            &origins,
+            0,
+            |_| ConstantRandom,
+            0.0,
+        );
+        assert_eq!(tapered.nodes_iter().count(), 12);
+    }
+
+    #[test]
+    fn test_edge_taper() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let lengths = |edge_taper: Option<(f64, f64)>| -> Vec<f64> {
+            let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+                .bounds(Site::new(-1.0, -1.0), Site::new(4.0, 1.0));
+            if let Some((margin, min_scale)) = edge_taper {
+                builder = builder.edge_taper(margin, min_scale);
+            }
+            let network = builder
+                .add_origin(Site::default(), std::f64::consts::PI * 0.5, None)
+                .unwrap()
+                .iterate_as_possible(&mut ConstantRandom)
+                .path_network;
+            let mut xs = network
+                .nodes_iter()
+                .map(|(_, node)| node.site.x)
+                .filter(|x| *x >= 0.0)
+                .collect::<Vec<_>>();
+            xs.sort_by(f64::total_cmp);
+            xs.windows(2).map(|w| w[1] - w[0]).collect()
+        };
+
+        // stops abruptly at the bounds
+        assert_eq!(lengths(None), vec![1.0, 1.0, 1.0, 1.0]);
+        // the paths are shortened near the bounds
+        let tapered = lengths(Some((2.0, 0.5)));
+        assert!(tapered.len() > 4);
+        assert!(tapered.windows(2).all(|w| w[1] <= w[0]));
+        assert!(tapered.iter().all(|length| *length >= 0.5 - 1e-9));
+    }
+
+    #[test]
+    fn test_edge_rules_summary() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 2.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let node =
+            |x: f64, y: f64| TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false);
+        let existing0 = builder.path_network.add_node(node(1.0, -1.0));
+        let existing1 = builder.path_network.add_node(node(1.0, 1.0));
+        builder.path_network.add_path(existing0, existing1);
+
+        let builder = builder
+            .add_origin(
+                Site::default(),
+                std::f64::consts::PI * 0.5,
+                Some(Stage::from_num(1)),
+            )
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+
+        let network = &builder.path_network;
+        let origin = network.search_nearest_node(Site::default()).unwrap();
+        let intersection = network.search_nearest_node(Site::new(1.0, 0.0)).unwrap();
+        assert_eq!(network.neighbors_iter(intersection).unwrap().count(), 3);
+
+        let summary = builder.edge_rules_summary(origin, intersection).unwrap();
+        assert_eq!(summary.stage, Stage::from_num(1));
+        assert_eq!(summary.path_normal_length, 1.0);
+        // the paths which were not created by the builder
+        assert!(builder
+            .edge_rules_summary(intersection, existing0)
+            .is_none());
+        assert!(builder.edge_rules_summary(origin, existing0).is_none());
+    }
+
+    #[test]
+    fn test_hooks() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 2.5,
+        };
+        let mut added_nodes = Vec::new();
+        let mut added_paths = Vec::new();
+        let builder = TransportBuilder::new(&provider, &provider, &provider)
+            .on_node_added(|node_id, _| added_nodes.push(node_id))
+            .on_path_added(|path| added_paths.push(path))
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+        let node_count = builder.path_network.nodes_iter().count();
+        drop(builder);
+
+        // the origin and 2 nodes for each direction
+        assert_eq!(node_count, 5);
+        assert_eq!(added_nodes.len(), 5);
+        assert_eq!(added_paths.len(), 4);
+    }
+
+    #[test]
+    fn test_step() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 1.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap();
+        let origin = builder
+            .path_network
+            .search_nearest_node(Site::default())
+            .unwrap();
+
+        let outcome = builder.step(&mut ConstantRandom).unwrap();
+        assert_eq!(outcome.added_nodes.len(), 1);
+        assert_eq!(outcome.added_paths, vec![(origin, outcome.added_nodes[0])]);
+        assert!(outcome.removed_paths.is_empty());
+
+        let mut steps = 1;
+        while builder.step(&mut ConstantRandom).is_some() {
+            steps += 1;
+        }
+        // the paths from the origin cannot be extended beyond the bound
+        assert_eq!(steps, 2);
+        assert_eq!(builder.path_network.nodes_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_iterate_mut() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 3.5,
+        };
+        let create = || {
+            TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin(Site::default(), 0.0, None)
+                .unwrap()
+        };
+        let count = |builder: &TransportBuilder<_, _, _>| builder.path_network.nodes_iter().count();
+
+        let mut builder = create();
+        builder.iterate_mut(&mut ConstantRandom);
+        assert_eq!(
+            count(&builder),
+            count(&create().iterate(&mut ConstantRandom))
+        );
+        builder.iterate_n_times_mut(2, &mut ConstantRandom);
+        assert_eq!(
+            count(&builder),
+            count(&create().iterate_n_times(3, &mut ConstantRandom))
+        );
+        builder.iterate_as_possible_mut(&mut ConstantRandom);
+        assert_eq!(count(&builder), 7);
+    }
+
+    #[test]
+    fn test_iterate_as_possible_capped() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 3.5,
+        };
+        let create = || {
+            TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin(Site::default(), 0.0, None)
+                .unwrap()
+        };
+
+        let (builder, finished) = create().iterate_as_possible_capped(2, &mut ConstantRandom);
+        assert!(!finished);
+        assert_eq!(builder.path_network.nodes_iter().count(), 3);
+
+        let (builder, finished) = create().iterate_as_possible_capped(100, &mut ConstantRandom);
+        assert!(finished);
+        assert_eq!(builder.path_network.nodes_iter().count(), 7);
+    }
+
+    #[test]
+    fn test_reset() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 3.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_n_times(2, &mut ConstantRandom);
+        builder.reset();
+        assert_eq!(builder.path_network.nodes_iter().count(), 0);
+        assert!(builder.frontier_nodes().is_empty());
+
+        let builder = builder
+            .add_origin(Site::new(0.0, 1.0), std::f64::consts::PI * 0.5, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+        assert_eq!(builder.path_network.nodes_iter().count(), 7);
+    }
+
+    #[test]
+    fn test_forbid_branch() {
+        let count = |forbid_branch: bool| {
+            let provider = FlatProvider {
+                rules: TransportRules::default()
+                    .path_normal_length(1.0)
+                    .branch_rules(BranchRules {
+                        branch_density: 1.0,
+                        ..Default::default()
+                    })
+                    .forbid_branch(forbid_branch),
+                bound: 2.5,
+            };
+            let builder = TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin(Site::default(), 0.0, None)
+                .unwrap()
+                .iterate_as_possible(&mut ConstantRandom);
+            builder.path_network.nodes_iter().count()
+        };
+        assert!(count(false) > 5);
+        assert_eq!(count(true), 5);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let provider = FlatProvider {
+            rules: TransportRules::default(),
+            bound: f64::MAX,
+        };
+        let builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap();
+        assert!(builder.frontier_nodes().is_empty());
+        assert_eq!(
+            builder.rules_error(),
+            Some(RulesError::NonPositivePathLength(0.0))
+        );
+
+        // bridges are not created, but the paths are extended
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .bridge_rules(BridgeRules {
+                    max_bridge_length: 1.0,
+                    ..Default::default()
+                }),
+            bound: 10.0,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap();
+        assert_eq!(
+            builder.rules_error(),
+            Some(RulesError::BridgeWithoutCheckStep)
+        );
+        assert!(!builder.frontier_nodes().is_empty());
+        builder.iterate_as_possible_mut(&mut ConstantRandom);
+        assert!(builder.path_network.node_count() > 1);
+
+        builder.reset();
+        assert_eq!(builder.rules_error(), None);
+    }
+
+    #[test]
+    fn test_boundary() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        // triangle narrowing to the east
+        let boundary = Boundary::new(vec![
+            Site::new(-0.5, -3.0),
+            Site::new(5.5, 0.0),
+            Site::new(-0.5, 3.0),
+        ]);
+        let builder = TransportBuilder::new(&provider, &provider, &provider)
+            .with_boundary(boundary.clone())
+            .add_origin(Site::default(), std::f64::consts::PI * 0.5, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+
+        assert!(builder
+            .path_network
+            .nodes_iter()
+            .all(|(_, node)| boundary.contains(&node.site)));
+        // the origin and 5 nodes to the east
+        assert_eq!(builder.path_network.nodes_iter().count(), 6);
+    }
+
+    #[test]
+    fn test_min_edge_length() {
+        let grown_neighbors = |min_edge_length: f64| {
+            let provider = FlatProvider {
+                rules: TransportRules::default()
+                    .path_normal_length(1.0)
+                    .min_edge_length(min_edge_length),
+                bound: f64::MAX,
+            };
+            let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+            let node =
+                |x: f64, y: f64| TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false);
+            // the existing path just in front of the start node
+            let left = builder.path_network.add_node(node(-1.0, -0.05));
+            let right = builder.path_network.add_node(node(1.0, -0.05));
+            builder.path_network.add_path(left, right);
+            let start = builder.path_network.add_node(node(0.0, 0.0));
+
+            builder
+                .seed_from_node(start, 0.0, None, &mut ConstantRandom)
+                .unwrap();
+            let builder = builder.iterate(&mut ConstantRandom);
+            builder
+                .path_network
+                .neighbors_iter(start)
+                .map_or(0, |neighbors| neighbors.count())
+        };
+
+        assert_eq!(grown_neighbors(0.0), 1);
+        // the intersection is too close to the start node
+        assert_eq!(grown_neighbors(0.1), 0);
+    }
+
+    #[test]
+    fn test_branch_offset() {
+        struct SlopedProvider;
+
+        impl TerrainProvider for SlopedProvider {
+            fn get_elevation(&self, site: &Site) -> Option<f64> {
+                // flat along the y axis, and steep along the x axis
+                Some(site.x)
+            }
+        }
+
+        let stump_start_sites = |rules: TransportRules, terrain_provider: &dyn TerrainProvider| {
+            let provider = FlatProvider {
+                rules,
+                bound: f64::MAX,
+            };
+            let mut builder = TransportBuilder::new(&provider, terrain_provider, &provider);
+            builder.push_origin(Site::default(), 0.0, None).unwrap();
+            builder.iterate_mut(&mut ConstantRandom);
+            builder
+                .stump_heap
+                .iter()
+                .map(|stump| {
+                    builder
+                        .path_network
+                        .get_node(stump.get_node_id())
+                        .unwrap()
+                        .site
+                })
+                .collect::<BTreeSet<_>>()
+        };
+        let rules = |branch_offset: f64| {
+            TransportRules::default()
+                .path_normal_length(1.0)
+                .branch_rules(BranchRules {
+                    branch_density: 1.0,
+                    branch_offset,
+                    ..Default::default()
+                })
+        };
+
+        let flat = FlatProvider {
+            rules: TransportRules::default(),
+            bound: f64::MAX,
+        };
+
+        // the straight path and two branches start at the same site
+        assert_eq!(stump_start_sites(rules(0.0), &flat).len(), 2);
+
+        // the branches start at the sites offset perpendicular to the path (along the x axis)
+        let sites = stump_start_sites(rules(0.2), &flat);
+        assert_eq!(sites.len(), 4);
+        let offset_sites = sites
+            .iter()
+            .filter(|site| site.x.abs() > 1e-9)
+            .collect::<Vec<_>>();
+        assert_eq!(offset_sites.len(), 2);
+        offset_sites.iter().for_each(|site| {
+            assert!((site.y.abs() - 1.0).abs() < 1e-9);
+            assert!((site.x.abs() - 0.2).abs() < 1e-9);
+        });
+
+        // the offset paths shorter than the minimum length are not created
+        let sites = stump_start_sites(rules(0.2).min_edge_length(0.3), &flat);
+        assert_eq!(sites.len(), 2);
+
+        // the offset paths exceeding the slope limit are not created
+        let sites = stump_start_sites(
+            rules(0.2).path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(0.5)),
+            &SlopedProvider,
+        );
+        assert_eq!(sites.len(), 2);
+    }
+
+    #[test]
+    fn test_stage_from_terrain() {
+        let stage_from_terrain = StageFromTerrain::new(vec![0.1, 0.3]);
+        assert_eq!(stage_from_terrain.stage(0.0), Stage::from_num(0));
+        assert_eq!(stage_from_terrain.stage(-0.2), Stage::from_num(1));
+        assert_eq!(stage_from_terrain.stage(0.5), Stage::from_num(2));
+
+        struct SlopedProvider;
+
+        impl TerrainProvider for SlopedProvider {
+            fn get_elevation(&self, site: &Site) -> Option<f64> {
+                // flat along the x axis, and steep along the y axis
+                Some(site.y * 0.2)
+            }
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let origin_stage = |angle_radian: f64, stage: Option<Stage>| {
+            let mut builder = TransportBuilder::new(&provider, &SlopedProvider, &provider)
+                .stage_from_terrain(stage_from_terrain.clone());
+            let (origin, _) = builder
+                .push_origin(Site::default(), angle_radian, stage)
+                .unwrap();
+            builder.path_network.get_node(origin).unwrap().stage
+        };
+
+        assert_eq!(origin_stage(0.0, None), Stage::from_num(1));
+        assert_eq!(
+            origin_stage(std::f64::consts::PI * 0.5, None),
+            Stage::from_num(0)
+        );
+        // the specified stage is preferred
+        assert_eq!(
+            origin_stage(0.0, Some(Stage::from_num(3))),
+            Stage::from_num(3)
+        );
+    }
+
+    #[test]
+    fn test_grow_toward() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 5.0,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let start = builder.path_network.add_node(TransportNode::new(
+            Site::default(),
+            0.0,
+            Stage::default(),
+            false,
+        ));
+
+        let target = Site::new(3.0, -2.5);
+        let end = builder
+            .grow_toward(start, target, None, &mut ConstantRandom)
+            .unwrap();
+        assert_eq!(builder.path_network.get_node(end).unwrap().site, target);
+        assert!(builder.path_network.is_connected(start, end));
+        assert_eq!(builder.path_network.ancestry(end).last(), Some(&start));
+        // no stumps are left to be extended
+        assert!(builder.frontier_nodes().is_empty());
+
+        // blocked by the coastline
+        assert_eq!(
+            builder.grow_toward(end, Site::new(10.0, 0.0), None, &mut ConstantRandom),
+            None
+        );
+
+        // connected to the existing node at the target
+        let near_target = builder.path_network.add_node(TransportNode::new(
+            Site::new(3.0, -1.8),
+            0.0,
+            Stage::default(),
+            false,
+        ));
+        let nodes_count = builder.path_network.node_count();
+        assert_eq!(
+            builder.grow_toward(near_target, target, None, &mut ConstantRandom),
+            Some(end)
+        );
+        assert_eq!(builder.path_network.node_count(), nodes_count);
+        assert!(builder.path_network.has_path(near_target, end));
+    }
+
+    #[test]
+    fn test_grow_toward_across_path() {
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .path_extra_length_for_intersection(0.1),
+            bound: f64::MAX,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let node =
+            |x: f64, y: f64| TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false);
+        let start = builder.path_network.add_node(node(0.0, 0.0));
+        // the path lies between the start and the target, within the last segment
+        let wall0 = builder.path_network.add_node(node(0.5, -1.0));
+        let wall1 = builder.path_network.add_node(node(0.5, 1.0));
+        builder.path_network.add_path(wall0, wall1);
+
+        let target = Site::new(0.8, 0.0);
+        let end = builder
+            .grow_toward(start, target, None, &mut ConstantRandom)
+            .unwrap();
+        assert_eq!(builder.path_network.get_node(end).unwrap().site, target);
+
+        // the path is split at the crossing instead of crossing it
+        assert!(!builder.path_network.has_path(wall0, wall1));
+        let crossing = builder
+            .path_network
+            .neighbors_iter(wall0)
+            .unwrap()
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        assert_eq!(crossing.len(), 1);
+        assert_eq!(
+            builder.path_network.get_node(crossing[0]).unwrap().site,
+            Site::new(0.5, 0.0)
+        );
+        assert!(builder.path_network.has_path(crossing[0], end));
+        assert!(builder.path_network.has_path(start, crossing[0]));
+    }
+
+    #[test]
+    fn test_ancestry() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let (origin, _) = builder.push_origin(Site::default(), 0.0, None).unwrap();
+        builder.iterate_n_times_mut(6, &mut ConstantRandom);
+
+        // every node created by the growth is traced back to the origin
+        builder.path_network.nodes_iter().for_each(|(node_id, _)| {
+            let ancestry = builder.path_network.ancestry(node_id);
+            assert_eq!(ancestry.first(), Some(&node_id));
+            assert_eq!(ancestry.last(), Some(&origin));
+        });
+    }
+
+    #[test]
+    fn test_stage_styles() {
+        let provider = FlatProvider {
+            rules: TransportRules::default(),
+            bound: f64::MAX,
+        };
+        let mut builder =
+            TransportBuilder::new(&provider, &provider, &provider).stage_styles(BTreeMap::from([
+                (Stage::from_num(0), StageStyle::new(2.0, "main")),
+            ]));
+
+        let node = |x: f64, stage: usize| {
+            TransportNode::new(Site::new(x, 0.0), 0.0, Stage::from_num(stage), false)
+        };
+        let main_start = builder.path_network.add_node(node(0.0, 0));
+        let main_end = builder.path_network.add_node(node(1.0, 0));
+        let branch_end = builder.path_network.add_node(node(2.0, 1));
+        builder.path_network.add_path(main_start, main_end);
+        builder.path_network.add_path(main_end, branch_end);
+
+        assert_eq!(
+            builder.path_style(main_start, main_end),
+            Some(&StageStyle::new(2.0, "main"))
+        );
+        // no style for the stage
+        assert_eq!(builder.path_style(main_end, branch_end), None);
+        // no path
+        assert_eq!(builder.path_style(main_start, branch_end), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "must return a value in [0, 1)")]
+    fn test_random_out_of_range() {
+        struct BrokenRandom;
+
+        impl RandomF64Provider for BrokenRandom {
+            fn gen_f64(&mut self) -> f64 {
+                1.5
+            }
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate(&mut BrokenRandom);
+    }
+
+    #[test]
+    fn test_reactivate_near() {
+        struct GrowingProvider {
+            bound: std::cell::Cell<f64>,
+        }
+
+        impl TerrainProvider for GrowingProvider {
+            fn get_elevation(&self, site: &Site) -> Option<f64> {
+                (site.distance(&Site::default()) <= self.bound.get()).then_some(0.0)
+            }
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: f64::MAX,
+        };
+        let terrain = GrowingProvider {
+            bound: std::cell::Cell::new(2.5),
+        };
+        let mut builder = TransportBuilder::new(&provider, &terrain, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+        let node_count = builder.path_network.nodes_iter().count();
+
+        // nothing to regrow in the unchanged area
+        assert_eq!(
+            builder.reactivate_near(Site::default(), 10.0, &mut ConstantRandom),
+            0
+        );
+
+        terrain.bound.set(5.5);
+        // the dead ends are out of the radius
+        assert_eq!(
+            builder.reactivate_near(Site::new(10.0, 10.0), 1.0, &mut ConstantRandom),
+            0
+        );
+        assert_eq!(
+            builder.reactivate_near(Site::default(), 10.0, &mut ConstantRandom),
+            2
+        );
+        builder.iterate_as_possible_mut(&mut ConstantRandom);
+        assert_eq!(builder.path_network.nodes_iter().count(), node_count + 6);
+    }
+
+    #[test]
+    fn test_new_dyn() {
+        struct Generator<'a> {
+            builder: DynTransportBuilder<'a>,
+        }
+
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 2.5,
+        };
+        let rules_provider: &dyn TransportRulesProvider = &provider;
+        let generator = Generator {
+            builder: TransportBuilder::new_dyn(rules_provider, &provider, &provider)
+                .add_origin(Site::default(), 0.0, None)
+                .unwrap(),
+        };
+        let dyn_network = generator
+            .builder
+            .iterate_as_possible(&mut ConstantRandom)
+            .path_network;
+
+        let network = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom)
+            .path_network;
+        assert_eq!(
+            dyn_network.nodes_iter().collect::<Vec<_>>(),
+            network.nodes_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_add_origin_with_result() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 1.5,
+        };
+        let seed = |site: Site| {
+            TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin_with_result(site, std::f64::consts::PI * 0.5, None)
+                .map(|(_, seed_result)| seed_result)
+        };
+
+        assert_eq!(
+            seed(Site::default()),
+            Some(SeedResult {
+                forward: true,
+                backward: true
+            })
+        );
+        // on the coastline
+        assert_eq!(
+            seed(Site::new(1.0, 0.0)),
+            Some(SeedResult {
+                forward: false,
+                backward: true
+            })
+        );
+        assert_eq!(seed(Site::new(2.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_probe_node() {
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_direction_rules(PathDirectionRules {
+                max_radian: std::f64::consts::PI * 0.5,
+                comparison_step: 3,
+                ..Default::default()
+            });
+        let provider = FlatProvider {
+            rules: rules.clone(),
+            bound: 1.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let node_id = builder.path_network.add_node(TransportNode::new(
+            Site::new(1.0, 0.0),
+            0.0,
+            Stage::default(),
+            false,
+        ));
+
+        // north-west, north, and north-east of the node
+        let results = builder
+            .probe_node(node_id, 0.0, &mut ConstantRandom)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect::<Vec<_>>();
+        let accepted = ProbeResult::Accepted {
+            evaluation: 0.0,
+            creates_bridge: false,
+        };
+        assert_eq!(results, vec![accepted, accepted, ProbeResult::NoElevation]);
+        // the network is not changed
+        assert!(builder.frontier_nodes().is_empty());
+        assert_eq!(builder.path_network.nodes_iter().count(), 1);
+
+        let provider = FlatProvider {
+            rules: rules.path_slope_elevation_diff_limit(ElevationDiffLimit::AlwaysDeny),
+            bound: 1.5,
+        };
+        let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+        let node_id = builder.path_network.add_node(TransportNode::new(
+            Site::new(1.0, 0.0),
+            0.0,
+            Stage::default(),
+            false,
+        ));
+        let results = builder
+            .probe_node(node_id, 0.0, &mut ConstantRandom)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                ProbeResult::Slope,
+                ProbeResult::Slope,
+                ProbeResult::NoElevation
+            ]
+        );
+        assert!(builder
+            .probe_node(NodeId::new(100), 0.0, &mut ConstantRandom)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_seeded() {
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .path_direction_rules(PathDirectionRules {
+                    max_radian: std::f64::consts::PI * 0.5,
+                    comparison_step: 3,
+                    ..Default::default()
+                })
+                .branch_rules(BranchRules {
+                    branch_density: 0.3,
+                    ..Default::default()
+                }),
+            bound: 8.0,
+        };
+        let generate = |seed: u64| {
+            TransportBuilder::seeded(seed, &provider, &provider, &provider)
+                .add_origin(Site::default(), 0.0, None)
+                .unwrap()
+                .iterate_as_possible()
+                .snapshot()
+                .0
+                .unwrap()
+        };
+
+        let network = generate(1);
+        assert!(network.nodes_iter().count() > 10);
+        assert_eq!(network.topology_hash(), generate(1).topology_hash());
+        assert_ne!(network.topology_hash(), generate(2).topology_hash());
+
+        let mut rng = SeededRandom::new(3);
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.gen_f64())));
+    }
+
+    #[test]
+    fn test_waypoints() {
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .path_direction_rules(PathDirectionRules {
+                    max_radian: std::f64::consts::PI * 0.5,
+                    comparison_step: 5,
+                    ..Default::default()
+                }),
+            bound: f64::MAX,
+        };
+        let first_site = |waypoints: &[Site]| {
+            let mut builder = TransportBuilder::new(&provider, &provider, &provider);
+            let start = builder.path_network.add_node(TransportNode::new(
+                Site::default(),
+                0.0,
+                Stage::default(),
+                false,
+            ));
+            builder.with_waypoints(waypoints, 10.0, 1.0);
+            builder
+                .seed_from_node(start, 0.0, None, &mut ConstantRandom)
+                .unwrap();
+            let outcome = builder.step(&mut ConstantRandom).unwrap();
+            builder
+                .path_network
+                .get_node(outcome.added_nodes[0])
+                .unwrap()
+                .site
+        };
+
+        // attracted to the north-west
+        let site = first_site(&[Site::new(-3.0, -3.0), Site::new(20.0, 0.0)]);
+        assert!(site.x < -0.7 && site.y < -0.7);
+        // the waypoint is out of the radius
+        let site = first_site(&[Site::new(-30.0, -30.0)]);
+        assert!(site.x > 0.7 && site.y < -0.7);
+        let site = first_site(&[]);
+        assert!(site.x > 0.7 && site.y < -0.7);
+    }
+
+    #[test]
+    fn test_deterministic_iteration() {
+        // all stumps have the same priority
+        let provider = FlatProvider {
+            rules: TransportRules::default()
+                .path_normal_length(1.0)
+                .path_extra_length_for_intersection(0.3)
+                .path_direction_rules(PathDirectionRules {
+                    max_radian: std::f64::consts::PI * 0.25,
+                    comparison_step: 3,
+                    ..Default::default()
+                })
+                .branch_rules(BranchRules {
+                    branch_density: 0.4,
+                    ..Default::default()
+                }),
+            bound: 6.0,
+        };
+        let generate = || {
+            let mut rng = SeededRandom::new(7);
+            TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin(Site::new(-1.0, 0.0), 0.0, None)
+                .unwrap()
+                .add_origin(Site::new(1.0, 0.0), 0.0, None)
+                .unwrap()
+                .iterate_as_possible(&mut rng)
+                .path_network
+                .parse()
+        };
+
+        let (nodes, paths) = generate();
+        assert!(nodes.len() > 10);
+        assert_eq!(generate(), (nodes, paths));
+    }
+
+    #[test]
+    fn test_stump_tie_break() {
+        // all stumps have the same priority
+        let stump = |node_id: usize, x: f64| {
+            Stump::new(
+                NodeId::new(node_id),
+                TransportNode::new(Site::new(x, 0.0), 0.0, Stage::default(), false),
+                TransportRules::default(),
+                PathMetrics::default(),
+                1.0,
+                false,
+            )
+        };
+        let stumps = vec![stump(2, 0.0), stump(0, 1.0), stump(1, 0.0), stump(0, 0.0)];
+        let pop_order = |stumps: Vec<Stump>| {
+            let mut heap = stumps.into_iter().collect::<BinaryHeap<_>>();
+            std::iter::from_fn(|| heap.pop())
+                .map(|stump| (stump.get_node_id(), stump.get_node_expected_end().site.x))
+                .collect::<Vec<_>>()
+        };
+
+        let order = pop_order(stumps.clone());
+        assert_eq!(
+            order,
+            vec![
+                (NodeId::new(0), 1.0),
+                (NodeId::new(0), 0.0),
+                (NodeId::new(1), 0.0),
+                (NodeId::new(2), 0.0),
+            ]
+        );
+        // the order does not depend on the order of insertion
+        assert_eq!(pop_order(stumps.into_iter().rev().collect()), order);
+    }
+
+    #[test]
+    fn test_add_origins() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 5.0,
+        };
+        let builder = TransportBuilder::new(&provider, &provider, &provider)
+            .add_origins([
+                (Site::new(-3.0, 0.0), 0.0, None),
+                // no elevation
+                (Site::new(10.0, 0.0), 0.0, None),
+                (Site::new(3.0, 0.0), 0.0, Some(Stage::from_num(1))),
+            ])
+            .unwrap();
+        let origins = builder
+            .path_network
+            .nodes_iter()
+            .map(|(_, node)| (node.site, node.stage))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            origins,
+            vec![
+                (Site::new(-3.0, 0.0), Stage::from_num(0)),
+                (Site::new(3.0, 0.0), Stage::from_num(1))
+            ]
+        );
+        assert_eq!(builder.frontier_nodes().len(), 2);
+
+        assert!(TransportBuilder::new(&provider, &provider, &provider)
+            .add_origins([(Site::new(10.0, 0.0), 0.0, None)])
+            .is_none());
+        assert!(TransportBuilder::new(&provider, &provider, &provider)
+            .add_origins([])
+            .is_none());
+    }
+
+    #[test]
+    fn test_grid_bias() {
+        // ratio of the paths along the grid axes
+        let aligned_ratio = |pull: f64| {
+            let provider = FlatProvider {
+                rules: TransportRules::default()
+                    .path_normal_length(1.0)
+                    .path_direction_rules(PathDirectionRules {
+                        max_radian: std::f64::consts::PI * 0.25,
+                        comparison_step: 3,
+                        grid_bias: Some(GridBias {
+                            base_radian: std::f64::consts::PI * 0.5,
+                            pull,
+                        }),
+                        ..Default::default()
+                    })
+                    .branch_rules(BranchRules {
+                        branch_density: 0.3,
+                        ..Default::default()
+                    }),
+                bound: 6.0,
+            };
+            let network = TransportBuilder::new(&provider, &provider, &provider)
+                .add_origin(Site::default(), 0.3, None)
+                .unwrap()
+                .iterate_as_possible(&mut SeededRandom::new(5))
+                .path_network;
+            let angles = network
+                .nodes_iter()
+                .flat_map(|(node_id, node)| {
+                    network
+                        .neighbors_iter(node_id)
+                        .into_iter()
+                        .flatten()
+                        .map(move |(_, neighbor)| node.site.get_angle(&neighbor.site))
+                })
+                .collect::<Vec<_>>();
+            let aligned = angles
+                .iter()
+                .filter(|angle| {
+                    let quarter = angle.radian() / (std::f64::consts::PI * 0.5);
+                    (quarter - quarter.round()).abs() < 1e-6
+                })
+                .count();
+            aligned as f64 / angles.len() as f64
+        };
+
+        // the paths from the origin (to the direction of 0.3 rad) are also snapped
+        assert!(aligned_ratio(1.0) > 0.9);
+        assert!(aligned_ratio(0.0) < 0.5);
+    }
+
+    #[test]
+    fn test_termination_probability() {
+        // the trunk (stage 0) creates branches (stage 1) at every node, and the branches are always terminated
+        struct CulDeSacProvider;
+
+        impl TransportRulesProvider for CulDeSacProvider {
+            fn get_rules(&self, _: &Site, stage: Stage, _: &PathMetrics) -> Option<TransportRules> {
+                let branch_rules = if stage.as_num() == 0 {
+                    BranchRules {
+                        branch_density: 1.0,
+                        staging_probability: 1.0,
+                        ..Default::default()
+                    }
+                } else {
+                    BranchRules {
+                        termination_probability: 1.0,
+                        ..Default::default()
+                    }
+                };
+                Some(
+                    TransportRules::default()
+                        .path_normal_length(1.0)
+                        .branch_rules(branch_rules),
+                )
+            }
+        }
+
+        let terrain = FlatProvider {
+            rules: TransportRules::default(),
+            bound: 3.5,
+        };
+        let builder = TransportBuilder::new(&CulDeSacProvider, &terrain, &terrain)
+            .add_origin(Site::default(), 0.0, None)
+            .unwrap()
+            .iterate_as_possible(&mut ConstantRandom);
+        let network = &builder.path_network;
+
+        let (trunk, branches): (Vec<_>, Vec<_>) = network
+            .nodes_iter()
+            .partition(|(_, node)| node.stage.as_num() == 0);
+        // the trunk is extended to the bound
+        assert_eq!(trunk.len(), 7);
+        // 2 branches from each node of the trunk except the origin
+        assert_eq!(branches.len(), 12);
+        // the branches end at their first nodes
+        assert!(branches.iter().all(|(node_id, _)| network
+            .neighbors_iter(*node_id)
+            .is_some_and(|neighbors| neighbors.count() == 1)));
+    }
+
+    #[test]
+    fn test_missing_elevation_policy() {
+        let provider = FlatProvider {
+            rules: TransportRules::default().path_normal_length(1.0),
+            bound: 5.5,
+        };
+        let grow = |policy: MissingElevationPolicy, n: usize| -> usize {
+            let mut builder = TransportBuilder::new(&provider, &provider, &provider)
+                .missing_elevation_policy(policy);
+            let origin = builder.path_network.add_node(TransportNode::new(
+                Site::default(),
+                0.0,
+                Stage::default(),
+                false,
+            ));
+            builder
+                .seed_from_node(
+                    origin,
+                    std::f64::consts::PI * 0.5,
+                    None,
+                    &mut ConstantRandom,
+                )
+                .unwrap();
+            builder
+                .iterate_n_times(n, &mut ConstantRandom)
+                .path_network
+                .nodes_iter()
+                .count()
+        };
+
+        // stops at the bound of the terrain
+        assert_eq!(grow(MissingElevationPolicy::Reject, 10), 6);
+        // extends beyond the bound of the terrain
+        assert_eq!(grow(MissingElevationPolicy::Flat(0.0), 10), 11);
+        assert_eq!(grow(MissingElevationPolicy::ExtrapolateLast, 10), 11);
     }
 }
@@ -2,6 +2,7 @@ pub mod growth_type;
 pub mod stump;
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use crate::{
         core::{
@@ -13,8 +14,14 @@ mod tests {
             params::{
                 metrics::PathMetrics,
                 numeric::Stage,
-                rules::{ElevationDiffLimit, TransportRules},
+                priority::PathPrioritizationFactors,
+                rules::{
+                    direction::{PathDirectionRules, TerrainFollowing},
+                    switchback::SwitchbackRules,
+                    ElevationDiffLimit, TransportRules,
+                },
             },
+            traits::{PathPrioritizator, RandomF64Provider, TerrainProvider},
         },
     };
 
@@ -246,6 +253,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_min_intersection_angle() {
+        let nodes = [create_node(0.0, 0.0), create_node(10.0, 0.0)];
+        let nodes_parsed = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, NodeId::new(i)))
+            .collect::<Vec<_>>();
+        let paths_parsed = vec![(nodes_parsed[0], nodes_parsed[1])];
+
+        // the path grazing the existing path at 0.05 radian
+        let node_start = create_node(1.0, -0.05);
+        let next_node = |min_intersection_angle: f64| {
+            let rules = TransportRules::default()
+                .path_normal_length(2.0)
+                .min_intersection_angle(min_intersection_angle);
+            let site_expected_end = node_start.site.extend(
+                Angle::new(std::f64::consts::PI * 0.5 + 0.05),
+                rules.path_normal_length,
+            );
+            Stump::new(
+                NodeId::new(10000),
+                TransportNode::new(site_expected_end, 0.0, Stage::default(), false),
+                rules,
+                PathMetrics::default(),
+                0.0,
+                false,
+            )
+            .determine_growth(&node_start, &nodes_parsed, &paths_parsed)
+            .next_node
+        };
+
+        assert!(matches!(next_node(0.0), NextNodeType::Intersect(_, _)));
+        assert!(matches!(next_node(0.04), NextNodeType::Intersect(_, _)));
+        assert!(matches!(next_node(0.1), NextNodeType::None));
+    }
+
     #[test]
     fn test_bridge() {
         let nodes = vec![
@@ -322,4 +366,256 @@ mod tests {
             panic!("Unexpected node type");
         }
     }
+
+    struct SlopeTerrain;
+
+    impl TerrainProvider for SlopeTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            Some(site.x)
+        }
+    }
+
+    struct ConstantRandom(f64);
+
+    impl RandomF64Provider for ConstantRandom {
+        fn gen_f64(&mut self) -> f64 {
+            self.0
+        }
+    }
+
+    struct FlatPrioritizator;
+
+    impl PathPrioritizator for FlatPrioritizator {
+        fn prioritize(&self, _: PathPrioritizationFactors) -> Option<f64> {
+            Some(0.0)
+        }
+    }
+
+    #[test]
+    fn test_terrain_following() {
+        let node = create_node(0.0, 0.0);
+        let create = |terrain_following: TerrainFollowing| -> Site {
+            let rules = TransportRules::default()
+                .path_normal_length(1.0)
+                .path_direction_rules(PathDirectionRules {
+                    max_radian: std::f64::consts::PI * 0.5,
+                    comparison_step: 3,
+                    terrain_following,
+                    grid_bias: None,
+                });
+            Stump::create(
+                &SlopeTerrain,
+                &FlatPrioritizator,
+                (&node, NodeId::new(0)),
+                Angle::new(std::f64::consts::PI * 0.25),
+                Stage::default(),
+                &rules,
+                &PathMetrics::default(),
+                &mut ConstantRandom(0.5),
+            )
+            .unwrap()
+            .get_node_expected_end()
+            .site
+        };
+
+        // Valley: follow the contour (x is not changed)
+        let site = create(TerrainFollowing::Valley(1.0));
+        assert_eq_f64!(site.distance(&Site::new(0.0, -1.0)), 0.0);
+
+        // Ridge: climb the slope (x is increased)
+        let site = create(TerrainFollowing::Ridge(1.0));
+        assert_eq_f64!(site.distance(&Site::new(1.0, 0.0)), 0.0);
+    }
+
+    struct SteepTerrain;
+
+    impl TerrainProvider for SteepTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            Some(-site.y * 10.0)
+        }
+    }
+
+    #[test]
+    fn test_switchback() {
+        let node = create_node(0.0, 0.0);
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(6.0));
+        let create = |rules: &TransportRules, metrics: &PathMetrics| -> Option<Stump> {
+            Stump::create(
+                &SteepTerrain,
+                &FlatPrioritizator,
+                (&node, NodeId::new(0)),
+                Angle::new(0.0),
+                Stage::default(),
+                rules,
+                metrics,
+                &mut ConstantRandom(0.5),
+            )
+        };
+
+        // The slope is too steep to climb straight.
+        assert!(create(&rules, &PathMetrics::default()).is_none());
+
+        let rules = rules.switchback_rules(SwitchbackRules {
+            turn_radian: std::f64::consts::PI / 3.0,
+            max_segments: 2,
+        });
+
+        let stump = create(&rules, &PathMetrics::default()).unwrap();
+        assert_eq!(stump.get_switchback_angle(), Some(Angle::new(0.0)));
+        let site = stump.get_node_expected_end().site;
+        assert!(site.x > 0.0 && site.y < 0.0);
+
+        // The next segment turns to the opposite side.
+        let metrics = PathMetrics::default().switchback_incremented();
+        let site = create(&rules, &metrics)
+            .unwrap()
+            .get_node_expected_end()
+            .site;
+        assert!(site.x < 0.0 && site.y < 0.0);
+
+        // Too many consecutive segments.
+        let metrics = metrics.switchback_incremented();
+        assert!(create(&rules, &metrics).is_none());
+    }
+
+    #[test]
+    fn test_switchback_missing_terrain() {
+        /// Flat terrain with a lake (no elevation) just in front of the origin.
+        struct LakeTerrain;
+
+        impl TerrainProvider for LakeTerrain {
+            fn get_elevation(&self, site: &Site) -> Option<f64> {
+                if site.x.abs() < 0.3 && site.y < -0.5 {
+                    None
+                } else {
+                    Some(0.0)
+                }
+            }
+        }
+
+        let node = create_node(0.0, 0.0);
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .switchback_rules(SwitchbackRules {
+                turn_radian: std::f64::consts::PI / 3.0,
+                max_segments: 2,
+            });
+
+        // The path stops at the lake without zig-zagging along it.
+        let stump = Stump::create(
+            &LakeTerrain,
+            &FlatPrioritizator,
+            (&node, NodeId::new(0)),
+            Angle::new(0.0),
+            Stage::default(),
+            &rules,
+            &PathMetrics::default(),
+            &mut ConstantRandom(0.5),
+        );
+        assert!(stump.is_none());
+    }
+
+    #[test]
+    fn test_path_length_jitter() {
+        let node = create_node(0.0, 0.0);
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_length_jitter(0.5);
+        let create = |random: f64| -> f64 {
+            Stump::create(
+                &SlopeTerrain,
+                &FlatPrioritizator,
+                (&node, NodeId::new(0)),
+                Angle::new(0.0),
+                Stage::default(),
+                &rules,
+                &PathMetrics::default(),
+                &mut ConstantRandom(random),
+            )
+            .unwrap()
+            .get_node_expected_end()
+            .site
+            .distance(&node.site)
+        };
+
+        assert_eq_f64!(create(0.0), 0.5);
+        assert_eq_f64!(create(0.5), 1.0);
+        assert_eq_f64!(create(0.75), 1.25);
+
+        // No random value is drawn without the jitter.
+        struct CountingRandom(usize);
+
+        impl RandomF64Provider for CountingRandom {
+            fn gen_f64(&mut self) -> f64 {
+                self.0 += 1;
+                0.0
+            }
+        }
+
+        let mut rng = CountingRandom(0);
+        let stump = Stump::create(
+            &SlopeTerrain,
+            &FlatPrioritizator,
+            (&node, NodeId::new(0)),
+            Angle::new(0.0),
+            Stage::default(),
+            &rules.clone().path_length_jitter(0.0),
+            &PathMetrics::default(),
+            &mut rng,
+        );
+        assert_eq_f64!(
+            stump
+                .unwrap()
+                .get_node_expected_end()
+                .site
+                .distance(&node.site),
+            1.0
+        );
+        assert_eq!(rng.0, 0);
+    }
+
+    #[test]
+    fn test_sample_points() {
+        /// Prioritizator which denies paths passing through the area around (0, -0.7).
+        struct LakePrioritizator;
+
+        impl PathPrioritizator for LakePrioritizator {
+            fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+                let lake = Site::new(0.0, -0.7);
+                if factors
+                    .sample_points
+                    .iter()
+                    .any(|site| site.distance(&lake) < 0.1)
+                {
+                    None
+                } else {
+                    Some(0.0)
+                }
+            }
+        }
+
+        let node = create_node(0.0, 0.0);
+        let create = |sample_count: usize| -> Option<Stump> {
+            let rules = TransportRules::default()
+                .path_normal_length(1.0)
+                .path_priority_sample_count(sample_count);
+            Stump::create(
+                &SlopeTerrain,
+                &LakePrioritizator,
+                (&node, NodeId::new(0)),
+                Angle::new(0.0),
+                Stage::default(),
+                &rules,
+                &PathMetrics::default(),
+                &mut ConstantRandom(0.5),
+            )
+        };
+
+        // the endpoints are not in the lake
+        assert!(create(0).is_some());
+        // the sampled site (0, -0.7) is in the lake
+        assert!(create(5).is_none());
+    }
 }
@@ -6,15 +6,33 @@ use crate::{
     transport::{
         node::TransportNode,
         params::{
-            metrics::PathMetrics, numeric::Stage, priority::PathPrioritizationFactors,
-            rules::TransportRules,
+            metrics::PathMetrics,
+            numeric::Stage,
+            priority::PathPrioritizationFactors,
+            rules::{GrowthMode, TransportRules},
         },
-        traits::{PathPrioritizator, TerrainProvider},
+        traits::{PathPrioritizator, RandomF64Provider, TerrainProvider},
     },
 };
 
 use super::growth_type::{BridgeNodeType, GrowthTypes, NextNodeType};
 
+/// Result of evaluating a direction to extend a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbeResult {
+    /// The path can be extended, with the evaluation (the higher, the more preferred).
+    Accepted {
+        evaluation: f64,
+        creates_bridge: bool,
+    },
+    /// The terrain has no elevation at the start or the end of the path.
+    NoElevation,
+    /// The elevation difference of the path exceeds the limit of the rules.
+    Slope,
+    /// The path prioritizator rejected the path.
+    Priority,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stump {
     /// node id which this stump is created for.
@@ -29,6 +47,8 @@ pub struct Stump {
     priority: f64,
     /// if the path is to be created is a bridge.
     creates_bridge: bool,
+    /// the original expected angle of the path if this stump is a switchback segment.
+    switchback_angle: Option<Angle>,
 }
 
 impl Eq for Stump {}
@@ -40,16 +60,36 @@ impl PartialOrd for Stump {
 }
 
 impl Ord for Stump {
+    /// Compare the stumps by the priority, breaking ties by the NodeId (the smaller is greater) and the expected end site.
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.priority.total_cmp(&other.priority)
+        self.priority
+            .total_cmp(&other.priority)
+            .then_with(|| other.node_id.cmp(&self.node_id))
+            .then_with(|| {
+                let (site, other_site) =
+                    (self.node_expected_end.site, other.node_expected_end.site);
+                site.x
+                    .total_cmp(&other_site.x)
+                    .then_with(|| site.y.total_cmp(&other_site.y))
+            })
     }
 }
 
 type RelatedNode<'a> = (&'a TransportNode, NodeId);
 
+/// Sample `count` sites along the path, at the midpoints of equally divided sections.
+fn sample_points(site_start: Site, site_end: Site, count: usize) -> Vec<Site> {
+    (0..count)
+        .map(|i| {
+            let t = (i as f64 + 0.5) / count as f64;
+            site_start + (site_end - site_start) * t
+        })
+        .collect()
+}
+
 impl Stump {
     /// Create a new stump.
-    pub(super) fn new(
+    pub(crate) fn new(
         node_id: NodeId,
         node_expected_end: TransportNode,
         rules: TransportRules,
@@ -64,11 +104,13 @@ impl Stump {
             metrics,
             priority,
             creates_bridge,
+            switchback_angle: None,
         }
     }
 
     /// Create a new stump for the given conditions.
-    pub fn create<TP, PP>(
+    #[allow(clippy::too_many_arguments)]
+    pub fn create<TP, PP, R>(
         terrain_provider: &TP,
         path_prioritizator: &PP,
         node_tuple: (&TransportNode, NodeId),
@@ -76,78 +118,256 @@ impl Stump {
         stage: Stage,
         rules: &TransportRules,
         metrics: &PathMetrics,
+        rng: &mut R,
     ) -> Option<Self>
     where
         TP: TerrainProvider,
-        PP: PathPrioritizator,
+        PP: PathPrioritizator + ?Sized,
+        R: RandomF64Provider,
     {
         let (node, node_id) = node_tuple;
 
-        let path_direction_rules = &rules.path_direction_rules;
-        let (estimated_end_site, creates_bridge) = angle_expected
-            .iter_range_around(
-                path_direction_rules.max_radian,
-                path_direction_rules.comparison_step,
+        let path_normal_length = Self::jittered_path_length(rules, rng);
+
+        let evaluate_angle = |angle: Angle| -> Result<(Site, f64, bool), ProbeResult> {
+            Self::evaluate_direction(
+                terrain_provider,
+                path_prioritizator,
+                node,
+                angle,
+                path_normal_length,
+                stage,
+                rules,
             )
-            .filter_map(|angle| {
-                for i in 0..=rules.bridge_rules.check_step {
-                    let bridge_path_length = if rules.bridge_rules.check_step == 0 {
-                        0.0
-                    } else {
-                        rules.bridge_rules.max_bridge_length * (i as f64)
-                            / (rules.bridge_rules.check_step as f64)
-                    };
-                    let path_length = rules.path_normal_length + bridge_path_length;
-                    let site_end = node.site.extend(angle, path_length);
-                    let creates_bridge = i > 0;
-                    if let Some(priority) =
-                        path_prioritizator.prioritize(PathPrioritizationFactors {
-                            site_start: node.site,
-                            site_end,
-                            path_length,
-                            stage,
-                            creates_bridge,
-                        })
-                    {
-                        if let (Some(elevation_start), Some(elevation_end)) = (
-                            terrain_provider.get_elevation(&node.site),
-                            terrain_provider.get_elevation(&site_end),
-                        ) {
-                            if rules
-                                .path_slope_elevation_diff_limit
-                                .check_slope((elevation_start, elevation_end), path_length)
-                            {
-                                return Some((site_end, priority, creates_bridge));
-                            }
-                        }
-                    }
-                }
-                None
-            })
+        };
+
+        let evaluations = Self::candidate_angles(angle_expected, rules)
+            .map(evaluate_angle)
+            .collect::<Vec<_>>();
+        let candidate = evaluations
+            .iter()
+            .filter_map(|evaluation| evaluation.as_ref().ok())
             .max_by(|(_, ev0, _), (_, ev1, _)| ev0.total_cmp(ev1))
-            .map(|(site, _, creates_bridge)| (site, creates_bridge))?;
+            .map(|(site, _, creates_bridge)| (*site, *creates_bridge));
+
+        let (estimated_end_site, creates_bridge, switchback_angle) =
+            if let Some((site, creates_bridge)) = candidate {
+                (site, creates_bridge, None)
+            } else {
+                // If all candidates are rejected only by the slope limit, try to create a switchback segment.
+                // The paths blocked by the terrain (e.g. the sea) or the prioritizator are not zig-zagged.
+                if !evaluations
+                    .iter()
+                    .all(|evaluation| matches!(evaluation, Err(ProbeResult::Slope)))
+                {
+                    return None;
+                }
+                let switchback_rules = rules.switchback_rules.as_ref()?;
+                if metrics.switchback_count >= switchback_rules.max_segments {
+                    return None;
+                }
+                let angle_clockwise =
+                    Angle::new(angle_expected.radian() + switchback_rules.turn_radian);
+                let angle_counterclockwise =
+                    Angle::new(angle_expected.radian() - switchback_rules.turn_radian);
+                // Alternate the side of the segment to zig-zag.
+                let angles = if metrics.switchback_count.is_multiple_of(2) {
+                    [angle_clockwise, angle_counterclockwise]
+                } else {
+                    [angle_counterclockwise, angle_clockwise]
+                };
+                let (site, _, creates_bridge) = angles
+                    .into_iter()
+                    .find_map(|angle| evaluate_angle(angle).ok())?;
+                (site, creates_bridge, Some(angle_expected))
+            };
 
         let priority = path_prioritizator.prioritize(PathPrioritizationFactors {
             site_start: node.site,
             site_end: estimated_end_site,
-            path_length: rules.path_normal_length,
+            path_length: path_normal_length,
             stage,
             creates_bridge,
+            sample_points: sample_points(
+                node.site,
+                estimated_end_site,
+                rules.path_priority_sample_count,
+            ),
         })?;
 
-        Some(Self::new(
-            node_id,
-            TransportNode::new(
-                estimated_end_site,
-                terrain_provider.get_elevation(&estimated_end_site)?,
-                stage,
-                false,
+        Some(Self {
+            switchback_angle,
+            ..Self::new(
+                node_id,
+                TransportNode::new(
+                    estimated_end_site,
+                    terrain_provider.get_elevation(&estimated_end_site)?,
+                    stage,
+                    false,
+                ),
+                rules.clone(),
+                metrics.clone(),
+                priority,
+                creates_bridge,
+            )
+        })
+    }
+
+    /// Vary the normal length of the path randomly by `path_length_jitter`.
+    ///
+    /// The random value is drawn only if the jitter is set,
+    /// so that the rules without jitter generate the same network as before.
+    fn jittered_path_length<R>(rules: &TransportRules, rng: &mut R) -> f64
+    where
+        R: RandomF64Provider,
+    {
+        if rules.path_length_jitter == 0.0 {
+            return rules.path_normal_length;
+        }
+        rules.path_normal_length * (1.0 + rules.path_length_jitter * (2.0 * rng.gen_f64() - 1.0))
+    }
+
+    /// Get the candidate angles of the path around the expected angle.
+    ///
+    /// The angles are pulled toward the grid axes if `grid_bias` of the direction rules is set.
+    fn candidate_angles(
+        angle_expected: Angle,
+        rules: &TransportRules,
+    ) -> impl Iterator<Item = Angle> + '_ {
+        let path_direction_rules = &rules.path_direction_rules;
+        let (max_radian, comparison_step) = match rules.growth_mode {
+            GrowthMode::Normal => (
+                path_direction_rules.max_radian,
+                path_direction_rules.comparison_step,
             ),
-            rules.clone(),
-            metrics.clone(),
-            priority,
-            creates_bridge,
-        ))
+            // only the straight path is evaluated
+            GrowthMode::StraightUntilBlocked => (0.0, 1),
+        };
+        angle_expected
+            .iter_range_around(max_radian, comparison_step)
+            .map(|angle| {
+                if let Some(grid_bias) = &path_direction_rules.grid_bias {
+                    grid_bias.pull(angle)
+                } else {
+                    angle
+                }
+            })
+    }
+
+    /// Evaluate the path extended from the node by the angle.
+    ///
+    /// If the path cannot be extended normally, bridges are tried by `bridge_rules`.
+    /// Return the end site, the evaluation, and whether the path creates a bridge,
+    /// or the reason why the path without bridges is rejected.
+    fn evaluate_direction<TP, PP>(
+        terrain_provider: &TP,
+        path_prioritizator: &PP,
+        node: &TransportNode,
+        angle: Angle,
+        path_normal_length: f64,
+        stage: Stage,
+        rules: &TransportRules,
+    ) -> Result<(Site, f64, bool), ProbeResult>
+    where
+        TP: TerrainProvider,
+        PP: PathPrioritizator + ?Sized,
+    {
+        let mut rejection = None;
+        for i in 0..=rules.bridge_rules.check_step {
+            let bridge_path_length = if i == 0 {
+                0.0
+            } else {
+                rules.bridge_rules.bridge_length(i)
+            };
+            let path_length = path_normal_length + bridge_path_length;
+            let site_end = node.site.extend(angle, path_length);
+            let creates_bridge = i > 0;
+            let result = (|| {
+                let priority = path_prioritizator
+                    .prioritize(PathPrioritizationFactors {
+                        site_start: node.site,
+                        site_end,
+                        path_length,
+                        stage,
+                        creates_bridge,
+                        sample_points: sample_points(
+                            node.site,
+                            site_end,
+                            rules.path_priority_sample_count,
+                        ),
+                    })
+                    .ok_or(ProbeResult::Priority)?;
+                let (elevation_start, elevation_end) = if let (Some(start), Some(end)) = (
+                    terrain_provider.get_elevation(&node.site),
+                    terrain_provider.get_elevation(&site_end),
+                ) {
+                    (start, end)
+                } else {
+                    return Err(ProbeResult::NoElevation);
+                };
+                if !rules
+                    .path_slope_elevation_diff_limit
+                    .check_slope((elevation_start, elevation_end), path_length)
+                {
+                    return Err(ProbeResult::Slope);
+                }
+                let evaluation = priority
+                    + rules
+                        .path_direction_rules
+                        .terrain_following
+                        .evaluate((elevation_start, elevation_end), path_length);
+                Ok((site_end, evaluation, creates_bridge))
+            })();
+            match result {
+                Ok(candidate) => return Ok(candidate),
+                Err(reason) => {
+                    rejection.get_or_insert(reason);
+                }
+            }
+        }
+        Err(rejection.unwrap_or(ProbeResult::Priority))
+    }
+
+    /// Evaluate the directions which `create` compares to extend the path from the node.
+    ///
+    /// Each direction is reported with whether the path can be extended or why it is rejected.
+    /// The switchback segments are not evaluated.
+    #[allow(clippy::too_many_arguments)]
+    pub fn probe<TP, PP, R>(
+        terrain_provider: &TP,
+        path_prioritizator: &PP,
+        node: &TransportNode,
+        angle_expected: Angle,
+        stage: Stage,
+        rules: &TransportRules,
+        rng: &mut R,
+    ) -> Vec<(Angle, ProbeResult)>
+    where
+        TP: TerrainProvider,
+        PP: PathPrioritizator + ?Sized,
+        R: RandomF64Provider,
+    {
+        let path_normal_length = Self::jittered_path_length(rules, rng);
+        Self::candidate_angles(angle_expected, rules)
+            .map(|angle| {
+                let result = match Self::evaluate_direction(
+                    terrain_provider,
+                    path_prioritizator,
+                    node,
+                    angle,
+                    path_normal_length,
+                    stage,
+                    rules,
+                ) {
+                    Ok((_, evaluation, creates_bridge)) => ProbeResult::Accepted {
+                        evaluation,
+                        creates_bridge,
+                    },
+                    Err(reason) => reason,
+                };
+                (angle, result)
+            })
+            .collect()
     }
 
     pub fn get_node_id(&self) -> NodeId {
@@ -166,6 +386,11 @@ impl Stump {
         &self.metrics
     }
 
+    /// Get the original expected angle of the path if this stump is a switchback segment.
+    pub fn get_switchback_angle(&self) -> Option<Angle> {
+        self.switchback_angle
+    }
+
     pub fn get_stage(&self) -> Stage {
         self.node_expected_end.stage
     }
@@ -179,10 +404,7 @@ impl Stump {
     ) -> Site {
         let path_length = site_expected_end.distance(&start_site);
         let scale = (path_length + self.rules.path_extra_length_for_intersection) / path_length;
-        Site::new(
-            start_site.x + (site_expected_end.x - start_site.x) * scale,
-            start_site.y + (site_expected_end.y - start_site.y) * scale,
-        )
+        start_site + (site_expected_end - start_site) * scale
     }
 
     /// Check elevation difference of two paths to determine if the paths can be grade separated.
@@ -208,6 +430,16 @@ impl Stump {
             .collect::<Vec<_>>()
     }
 
+    /// Get the acute angle between two line segments, in [0, π/2].
+    fn crossing_angle(line0: &LineSegment, line1: &LineSegment) -> f64 {
+        let direction0 = line0.1 - line0.0;
+        let direction1 = line1.1 - line1.0;
+        direction0
+            .cross(&direction1)
+            .abs()
+            .atan2(direction0.dot(&direction1).abs())
+    }
+
     fn check_slope(&self, node0: &TransportNode, node1: &TransportNode) -> bool {
         // slope check
         // if the elevation difference is too large, the path cannot be connected.
@@ -321,7 +553,15 @@ impl Stump {
 
             if let Some((crossing_node, path_nodes)) = crossing_path {
                 // if it cross the bridge, the path cannot be connected.
-                if path_nodes.0 .0.path_creates_bridge(path_nodes.1 .0) {
+                // if the intersection exceeds the maximum degree of nodes, the path cannot be connected.
+                // if the path crosses the existing path too shallowly, the path cannot be connected.
+                if path_nodes.0 .0.path_creates_bridge(path_nodes.1 .0)
+                    || self.rules.max_node_degree.is_some_and(|degree| degree < 3)
+                    || Self::crossing_angle(
+                        &search_line,
+                        &LineSegment::new(path_nodes.0 .0.site, path_nodes.1 .0.site),
+                    ) < self.rules.min_intersection_angle
+                {
                     return GrowthTypes {
                         next_node: NextNodeType::None,
                         bridge_node: BridgeNodeType::None,
@@ -1,5 +1,10 @@
 pub mod builder;
 mod growth;
+mod network;
 pub mod node;
+#[cfg(feature = "osm")]
+mod osm;
 pub mod params;
+pub mod seeding;
+pub mod svg;
 pub mod traits;
@@ -0,0 +1,953 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::{
+    container::path_network::{NodeId, PathNetwork},
+    geometry::{line_segment::LineSegment, site::Site},
+};
+
+use super::{
+    node::TransportNode,
+    params::numeric::Stage,
+    traits::{RandomF64Provider, TerrainProvider},
+};
+
+/// Check if the node can be removed by joining the paths from `prev` and to `next` into one path,
+/// without changing the stage and the bridge of the paths.
+fn is_removable_between(prev: &TransportNode, node: &TransportNode, next: &TransportNode) -> bool {
+    !node.is_bridge
+        && prev.path_stage(node) == node.path_stage(next)
+        && prev.path_creates_bridge(node) == node.path_creates_bridge(next)
+}
+
+/// Get the indices of the sites kept by the Douglas-Peucker algorithm, including both ends.
+fn douglas_peucker(sites: &[Site], tolerance: f64) -> Vec<usize> {
+    if sites.len() < 3 {
+        return (0..sites.len()).collect();
+    }
+    let mut kept = BTreeSet::from([0, sites.len() - 1]);
+    let mut ranges = vec![(0, sites.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let line = LineSegment::new(sites[first], sites[last]);
+        let farthest = (first + 1..last)
+            .map(|i| (i, line.get_distance(&sites[i])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                kept.insert(i);
+                ranges.push((first, i));
+                ranges.push((i, last));
+            }
+        }
+    }
+    kept.into_iter().collect()
+}
+
+impl PathNetwork<TransportNode> {
+    /// Get the paths in the network with their nodes.
+    ///
+    /// Each path is returned only once.
+    fn paths_with_nodes(&self) -> Vec<((NodeId, TransportNode), (NodeId, TransportNode))> {
+        self.nodes_iter()
+            .flat_map(|(node_id, node)| {
+                self.neighbors_iter(node_id)
+                    .into_iter()
+                    .flatten()
+                    .filter(move |(neighbor_id, _)| node_id < *neighbor_id)
+                    .map(move |(neighbor_id, neighbor)| {
+                        ((node_id, *node), (neighbor_id, *neighbor))
+                    })
+            })
+            .collect()
+    }
+
+    /// Create a network from nodes with full attributes (elevation, stage, and bridge) and paths between them.
+    ///
+    /// `paths` is the list of pairs of indices of `nodes`.
+    /// Return `None` if any path refers to a node which does not exist.
+    /// The created network can be used as the initial network of `TransportBuilder::with_path_network`.
+    pub fn from_detailed(nodes: Vec<TransportNode>, paths: &[(usize, usize)]) -> Option<Self> {
+        Self::from_opt(nodes, paths)
+    }
+
+    /// Get the total length of the paths.
+    pub fn total_length(&self) -> f64 {
+        self.paths_with_nodes()
+            .iter()
+            .map(|((_, start), (_, end))| start.site.distance(&end.site))
+            .sum()
+    }
+
+    /// Get the total length of the paths which create bridges.
+    pub fn total_bridge_length(&self) -> f64 {
+        self.paths_with_nodes()
+            .iter()
+            .filter(|((_, start), (_, end))| start.path_creates_bridge(end))
+            .map(|((_, start), (_, end))| start.site.distance(&end.site))
+            .sum()
+    }
+
+    /// Get the number of paths which create bridges.
+    pub fn bridge_count(&self) -> usize {
+        self.paths_with_nodes()
+            .iter()
+            .filter(|((_, start), (_, end))| start.path_creates_bridge(end))
+            .count()
+    }
+
+    /// Create a network which contains only the paths whose stage is `max_stage` or less.
+    ///
+    /// Nodes which are not connected to any of the paths are not contained.
+    pub fn subnetwork_by_stage(&self, max_stage: Stage) -> PathNetwork<TransportNode> {
+        let mut indices: BTreeMap<NodeId, usize> = BTreeMap::new();
+        let mut nodes = Vec::new();
+        let mut index_of = |node_id: NodeId, node: TransportNode| {
+            *indices.entry(node_id).or_insert_with(|| {
+                nodes.push(node);
+                nodes.len() - 1
+            })
+        };
+        let paths = self
+            .paths_with_nodes()
+            .into_iter()
+            .filter(|((_, start), (_, end))| start.path_stage(end) <= max_stage)
+            .map(|((start_id, start), (end_id, end))| {
+                (index_of(start_id, start), index_of(end_id, end))
+            })
+            .collect::<Vec<_>>();
+        PathNetwork::from(nodes, &paths).unwrap_or_default()
+    }
+
+    /// Resample the elevations of all nodes by `f`.
+    ///
+    /// The elevation of the node is not changed if `f` returns `None`.
+    pub fn update_elevations<F>(&mut self, f: F)
+    where
+        F: Fn(Site) -> Option<f64>,
+    {
+        self.modify_nodes(|node| {
+            if let Some(elevation) = f(node.site) {
+                *node = node.with_elevation(elevation);
+            }
+        });
+    }
+
+    /// Round the elevations of all nodes to the nearest multiples of `band`.
+    ///
+    /// The sites of the nodes are not changed.
+    /// This can be used to render the network on discrete elevation levels (e.g. contour maps).
+    /// Nothing is changed if `band` is not positive.
+    pub fn quantize_elevations(&mut self, band: f64) {
+        if band.is_nan() || band <= 0.0 {
+            return;
+        }
+        self.modify_nodes(|node| {
+            *node = node.with_elevation((node.elevation / band).round() * band);
+        });
+    }
+
+    /// Get the number of paths connected to the node.
+    fn degree(&self, node_id: NodeId) -> usize {
+        self.neighbors_iter(node_id)
+            .map_or(0, |neighbors| neighbors.count())
+    }
+
+    /// Move all nodes to the nearest points of the grid whose size is `grid_size`.
+    ///
+    /// Each node keeps its NodeId and paths. Nodes which land on the same grid point are merged into
+    /// the node moved there first, and the paths which become loops or duplicates are dropped.
+    /// Nothing is changed if `grid_size` is not positive or not finite.
+    pub fn snap_to_grid(&mut self, grid_size: f64) {
+        if !grid_size.is_finite() || grid_size <= 0.0 {
+            return;
+        }
+        let node_ids = self
+            .nodes_iter()
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        let mut cells: BTreeMap<(i64, i64), NodeId> = BTreeMap::new();
+        for node_id in node_ids {
+            let node = if let Some(node) = self.get_node(node_id) {
+                *node
+            } else {
+                continue;
+            };
+            let cell = (
+                (node.site.x / grid_size).round() as i64,
+                (node.site.y / grid_size).round() as i64,
+            );
+            if let Some(snapped_id) = cells.get(&cell) {
+                self.snap_nodes(node_id, *snapped_id);
+            } else {
+                self.replace_node(
+                    node_id,
+                    TransportNode {
+                        site: Site::new(cell.0 as f64 * grid_size, cell.1 as f64 * grid_size),
+                        ..node
+                    },
+                );
+                cells.insert(cell, node_id);
+            }
+        }
+    }
+
+    /// Split all paths into segments no longer than `spacing` by inserting evenly spaced nodes.
+    ///
+    /// The elevations of the inserted nodes are interpolated linearly.
+    pub fn densify(&mut self, spacing: f64) {
+        if spacing <= 0.0 {
+            return;
+        }
+        for ((start_id, start), (end_id, end)) in self.paths_with_nodes() {
+            let segments = (start.site.distance(&end.site) / spacing).ceil() as usize;
+            let mut current_id = start_id;
+            for i in 1..segments {
+                let t = i as f64 / segments as f64;
+                let site = start.site + (end.site - start.site) * t;
+                let node = TransportNode::new(
+                    site,
+                    start.elevation + (end.elevation - start.elevation) * t,
+                    start.path_stage(&end),
+                    start.path_creates_bridge(&end),
+                );
+                if let Some(node_id) = self.split_path(current_id, end_id, node) {
+                    current_id = node_id;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Remove the nodes in the middle of straight chains, joining their two paths into one path.
+    ///
+    /// A node is removed if it has exactly 2 paths and the turn angle (in radian) between the paths is less than `angle_tolerance`.
+    /// The turn angle is measured with the current paths, so the removals are applied one by one.
+    /// The elevations of the removed nodes are dropped.
+    ///
+    /// Bridge nodes, nodes between paths of different stages or between a bridge and a normal path,
+    /// and nodes whose neighbors are already connected are never removed.
+    pub fn collapse_collinear(&mut self, angle_tolerance: f64) {
+        let node_ids = self
+            .nodes_iter()
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        for node_id in node_ids {
+            let node = if let Some(node) = self.get_node(node_id) {
+                *node
+            } else {
+                continue;
+            };
+            let neighbors = if let Some(neighbors) = self.neighbors_iter(node_id) {
+                neighbors.map(|(id, node)| (id, *node)).collect::<Vec<_>>()
+            } else {
+                continue;
+            };
+            let ((prev_id, prev), (next_id, next)) = match neighbors.as_slice() {
+                [prev, next] => (*prev, *next),
+                _ => continue,
+            };
+            if !is_removable_between(&prev, &node, &next) || self.has_path(prev_id, next_id) {
+                continue;
+            }
+
+            let incoming = node.site - prev.site;
+            let outgoing = next.site - node.site;
+            let turn_angle = incoming
+                .cross(&outgoing)
+                .abs()
+                .atan2(incoming.dot(&outgoing));
+            if turn_angle >= angle_tolerance {
+                continue;
+            }
+
+            self.remove_node(node_id);
+            self.add_path(prev_id, next_id);
+        }
+    }
+
+    /// Relax the lengths of paths toward `target`.
+    ///
+    /// For each iteration,
+    ///  - paths longer than 1.5 times `target` are split at their midpoints.
+    ///  - paths shorter than 0.5 times `target` are collapsed by merging their endpoint
+    ///    which has exactly 2 paths (the middle of a chain) into the other endpoint.
+    ///    Junctions and dead ends are never moved.
+    ///
+    /// The iteration stops early if no path is changed.
+    /// Note that this function does not check whether the merged paths cross other paths.
+    pub fn relax_edge_lengths(&mut self, target: f64, iterations: usize) {
+        if target <= 0.0 {
+            return;
+        }
+        for _ in 0..iterations {
+            let mut changed = false;
+
+            // split long paths
+            let long_paths = self
+                .paths_with_nodes()
+                .into_iter()
+                .filter(|((_, start), (_, end))| start.site.distance(&end.site) > target * 1.5)
+                .collect::<Vec<_>>();
+            for ((start_id, start), (end_id, end)) in long_paths {
+                let middle_site = start.site.midpoint(&end.site);
+                let middle = TransportNode::new(
+                    middle_site,
+                    start.elevation_on_path(&end, middle_site),
+                    start.path_stage(&end),
+                    start.path_creates_bridge(&end),
+                );
+                changed |= self.split_path(start_id, end_id, middle).is_some();
+            }
+
+            // merge short paths
+            let mut short_paths = self
+                .paths_with_nodes()
+                .into_iter()
+                .map(|((start_id, start), (end_id, end))| {
+                    (start_id, end_id, start.site.distance(&end.site))
+                })
+                .filter(|(_, _, length)| *length < target * 0.5)
+                .collect::<Vec<_>>();
+            short_paths.sort_by(|a, b| a.2.total_cmp(&b.2));
+            for (start_id, end_id, _) in short_paths {
+                // the path may be already removed by the previous merge
+                if !self.has_path(start_id, end_id) {
+                    continue;
+                }
+                let (from, to) = if self.degree(end_id) == 2 {
+                    (end_id, start_id)
+                } else if self.degree(start_id) == 2 {
+                    (start_id, end_id)
+                } else {
+                    continue;
+                };
+                changed |= self.snap_nodes(from, to).is_some();
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Get the elevation profile of the path from `start` to `end` by the terrain.
+    ///
+    /// The path is sampled at `samples` sites at equal intervals including both ends,
+    /// and each sample is the pair of the distance from `start` and the elevation of the terrain.
+    /// The sites where the terrain has no elevation are skipped.
+    /// Return an empty vector if the path does not exist.
+    pub fn elevation_profile<TP>(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        samples: usize,
+        terrain: &TP,
+    ) -> Vec<(f64, f64)>
+    where
+        TP: TerrainProvider,
+    {
+        let (start_site, end_site) = match (self.get_node(start), self.get_node(end)) {
+            (Some(start_node), Some(end_node)) if self.has_path(start, end) => {
+                (start_node.site, end_node.site)
+            }
+            _ => return Vec::new(),
+        };
+        let length = start_site.distance(&end_site);
+        (0..samples)
+            .filter_map(|i| {
+                let t = if samples > 1 {
+                    i as f64 / (samples - 1) as f64
+                } else {
+                    0.0
+                };
+                let site = start_site + (end_site - start_site) * t;
+                Some((length * t, terrain.get_elevation(&site)?))
+            })
+            .collect()
+    }
+
+    /// Get the chains of nodes connected to exactly 2 paths, with the nodes of both ends.
+    ///
+    /// Each chain starts and ends at the nodes which are not connected to exactly 2 paths (junctions or dead ends).
+    /// Cycles which have no such nodes are not contained.
+    fn chains(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = BTreeSet::new();
+        let mut chains = Vec::new();
+        let ends = self
+            .nodes_iter()
+            .map(|(node_id, _)| node_id)
+            .filter(|node_id| self.degree(*node_id) != 2)
+            .collect::<Vec<_>>();
+        for end in ends {
+            let neighbors = self
+                .neighbors_iter(end)
+                .into_iter()
+                .flatten()
+                .map(|(neighbor_id, _)| neighbor_id)
+                .collect::<Vec<_>>();
+            for neighbor in neighbors {
+                if !visited.insert((end, neighbor)) {
+                    continue;
+                }
+                let mut chain = vec![end, neighbor];
+                while self.degree(chain[chain.len() - 1]) == 2 {
+                    let (previous, current) = (chain[chain.len() - 2], chain[chain.len() - 1]);
+                    let next = self
+                        .neighbors_iter(current)
+                        .into_iter()
+                        .flatten()
+                        .map(|(neighbor_id, _)| neighbor_id)
+                        .find(|neighbor_id| *neighbor_id != previous);
+                    if let Some(next) = next {
+                        chain.push(next);
+                    } else {
+                        break;
+                    }
+                }
+                visited.insert((chain[chain.len() - 1], chain[chain.len() - 2]));
+                chains.push(chain);
+            }
+        }
+        chains
+    }
+
+    /// Offset the middle nodes of long straight chains randomly to add organic variation.
+    ///
+    /// For each chain of nodes connected to exactly 2 paths whose nodes are on a straight line
+    /// and whose length is longer than `max_run`, the middle nodes are moved perpendicular to the line
+    /// by a random distance up to `amplitude`. The junctions and dead ends at both ends are never moved.
+    /// Note that this function does not check whether the moved paths cross other paths.
+    pub fn jitter_straight_runs<R>(&mut self, max_run: f64, amplitude: f64, rng: &mut R)
+    where
+        R: RandomF64Provider,
+    {
+        for chain in self.chains() {
+            if chain.len() < 3 {
+                continue;
+            }
+            let sites = chain
+                .iter()
+                .filter_map(|node_id| Some(self.get_node(*node_id)?.site))
+                .collect::<Vec<_>>();
+            let (first, last) = (sites[0], sites[sites.len() - 1]);
+            let length = first.distance(&last);
+            let line = LineSegment::new(first, last);
+            let is_straight = sites
+                .iter()
+                .all(|site| line.get_distance(site) <= length * 1e-9);
+            if length <= max_run || !is_straight {
+                continue;
+            }
+
+            let normal = first.get_angle(&last).right_clockwise();
+            for node_id in &chain[1..chain.len() - 1] {
+                let node = if let Some(node) = self.get_node(*node_id) {
+                    *node
+                } else {
+                    continue;
+                };
+                let offset = amplitude * (2.0 * rng.gen_f64() - 1.0);
+                self.replace_node(
+                    *node_id,
+                    TransportNode {
+                        site: node.site.extend(normal, offset),
+                        ..node
+                    },
+                );
+            }
+        }
+    }
+
+    /// Simplify the chains of nodes connected to exactly 2 paths by the Douglas-Peucker algorithm.
+    ///
+    /// The middle nodes of each chain which are within `tolerance` from the simplified line are removed,
+    /// and the remaining nodes are connected directly. The junctions and dead ends are never removed.
+    /// The chains are split at the bridge nodes and the nodes between paths of different stages,
+    /// which are also kept. The parts of the chains whose remaining nodes are already connected are not simplified.
+    /// Note that this function does not check whether the simplified paths cross other paths.
+    pub fn simplify(&mut self, tolerance: f64) {
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return;
+        }
+        for chain in self.chains() {
+            let nodes = chain
+                .iter()
+                .filter_map(|node_id| Some(*self.get_node(*node_id)?))
+                .collect::<Vec<_>>();
+            if nodes.len() != chain.len() {
+                continue;
+            }
+
+            // split the chain at the nodes which must be kept
+            let mut anchors = vec![0];
+            anchors.extend(
+                (1..chain.len() - 1)
+                    .filter(|i| !is_removable_between(&nodes[i - 1], &nodes[*i], &nodes[i + 1])),
+            );
+            anchors.push(chain.len() - 1);
+
+            for section in anchors.windows(2) {
+                let (first, last) = (section[0], section[1]);
+                let sites = nodes[first..=last]
+                    .iter()
+                    .map(|node| node.site)
+                    .collect::<Vec<_>>();
+                let kept = douglas_peucker(&sites, tolerance);
+                for pair in kept.windows(2) {
+                    let (start, end) = (first + pair[0], first + pair[1]);
+                    if end == start + 1
+                        || chain[start] == chain[end]
+                        || self.has_path(chain[start], chain[end])
+                    {
+                        continue;
+                    }
+                    for node_id in &chain[start + 1..end] {
+                        self.remove_node(*node_id);
+                    }
+                    self.add_path(chain[start], chain[end]);
+                }
+            }
+        }
+    }
+
+    /// Get the copies of the network simplified progressively by `tolerances` (see `simplify`).
+    ///
+    /// Each copy is simplified from the previous copy, so `tolerances` should be in ascending order
+    /// (from the most detailed level).
+    pub fn lod_pyramid(&self, tolerances: &[f64]) -> Vec<PathNetwork<TransportNode>> {
+        let mut network = self.clone();
+        tolerances
+            .iter()
+            .map(|tolerance| {
+                network.simplify(*tolerance);
+                network.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_node(x: f64, y: f64) -> TransportNode {
+        TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false)
+    }
+
+    #[test]
+    fn test_from_detailed() {
+        let nodes = vec![
+            TransportNode::new(Site::new(0.0, 0.0), 1.0, Stage::from_num(0), false),
+            TransportNode::new(Site::new(1.0, 0.0), 2.0, Stage::from_num(1), true),
+        ];
+        let network = PathNetwork::from_detailed(nodes.clone(), &[(0, 1)]).unwrap();
+        let imported = network
+            .nodes_iter()
+            .map(|(_, node)| *node)
+            .collect::<Vec<_>>();
+        assert_eq!(imported, nodes);
+        assert_eq!(network.paths_with_nodes().len(), 1);
+
+        assert!(PathNetwork::from_detailed(nodes, &[(0, 2)]).is_none());
+    }
+
+    #[test]
+    fn test_bridge_length() {
+        let nodes = vec![
+            create_node(0.0, 0.0),
+            TransportNode::new(Site::new(1.0, 0.0), 0.0, Stage::default(), true),
+            create_node(3.0, 0.0),
+            create_node(3.0, 4.0),
+        ];
+        let network = PathNetwork::from_detailed(nodes, &[(0, 1), (1, 2), (2, 3)]).unwrap();
+        assert_eq!(network.total_length(), 7.0);
+        assert_eq!(network.total_bridge_length(), 3.0);
+        assert_eq!(network.bridge_count(), 2);
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let nodes = vec![
+            create_node(0.1, 0.1),
+            create_node(0.9, 0.2),
+            create_node(1.1, -0.1),
+            create_node(1.2, 1.4),
+        ];
+        let mut network = PathNetwork::from(nodes, &[(0, 1), (1, 2), (0, 2), (2, 3)]).unwrap();
+        let node_ids = network
+            .nodes_iter()
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        let edge_id = network.edge_id(node_ids[0], node_ids[1]);
+
+        // nothing is changed by the invalid grid size
+        let sites = |network: &PathNetwork<TransportNode>| {
+            network
+                .nodes_iter()
+                .map(|(_, node)| node.site)
+                .collect::<Vec<_>>()
+        };
+        let original_sites = sites(&network);
+        network.snap_to_grid(f64::NAN);
+        network.snap_to_grid(0.0);
+        assert_eq!(sites(&network), original_sites);
+
+        network.snap_to_grid(1.0);
+
+        let mut sites = network
+            .nodes_iter()
+            .map(|(_, node)| node.site)
+            .collect::<Vec<_>>();
+        sites.sort();
+        assert_eq!(
+            sites,
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(1.0, 0.0),
+                Site::new(1.0, 1.0)
+            ]
+        );
+        // the path between the merged nodes is dropped, and the duplicated paths are merged
+        assert_eq!(network.paths_with_nodes().len(), 2);
+
+        // the nodes keep their NodeIds and paths, and the node merged into the other is removed
+        let site_of = |index: usize| network.get_node(node_ids[index]).map(|node| node.site);
+        assert_eq!(site_of(0), Some(Site::new(0.0, 0.0)));
+        assert_eq!(site_of(1), Some(Site::new(1.0, 0.0)));
+        assert_eq!(site_of(2), None);
+        assert_eq!(site_of(3), Some(Site::new(1.0, 1.0)));
+        assert_eq!(network.edge_id(node_ids[0], node_ids[1]), edge_id);
+        assert!(network.has_path(node_ids[1], node_ids[3]));
+    }
+
+    #[test]
+    fn test_subnetwork_by_stage() {
+        let node = |x: f64, stage: usize| {
+            TransportNode::new(Site::new(x, 0.0), 0.0, Stage::from_num(stage), false)
+        };
+        let nodes = vec![node(0.0, 0), node(1.0, 0), node(2.0, 1), node(3.0, 2)];
+        let network = PathNetwork::from(nodes, &[(0, 1), (1, 2), (2, 3)]).unwrap();
+
+        let trunk = network.subnetwork_by_stage(Stage::from_num(0));
+        assert_eq!(trunk.nodes_iter().count(), 2);
+        assert_eq!(trunk.paths_with_nodes().len(), 1);
+
+        let streets = network.subnetwork_by_stage(Stage::from_num(1));
+        assert_eq!(streets.nodes_iter().count(), 3);
+        assert_eq!(streets.paths_with_nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_update_elevations() {
+        let nodes = vec![create_node(0.0, 0.0), create_node(2.0, 0.0)];
+        let mut network = PathNetwork::from(nodes, &[(0, 1)]).unwrap();
+        network.update_elevations(|site| {
+            if site.x > 1.0 {
+                Some(site.x * 3.0)
+            } else {
+                None
+            }
+        });
+
+        let elevations = network
+            .nodes_iter()
+            .map(|(_, node)| node.elevation)
+            .collect::<Vec<_>>();
+        assert_eq!(elevations, vec![0.0, 6.0]);
+    }
+
+    #[test]
+    fn test_quantize_elevations() {
+        let nodes = vec![
+            TransportNode::new(Site::new(0.0, 0.0), 1.2, Stage::default(), false),
+            TransportNode::new(Site::new(1.0, 0.0), 3.9, Stage::default(), true),
+            TransportNode::new(Site::new(2.0, 0.0), -2.4, Stage::default(), false),
+        ];
+        let mut network = PathNetwork::from(nodes, &[(0, 1), (1, 2)]).unwrap();
+        network.quantize_elevations(2.0);
+
+        let nodes = network
+            .nodes_iter()
+            .map(|(_, node)| (node.site, node.elevation))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            vec![
+                (Site::new(0.0, 0.0), 2.0),
+                (Site::new(1.0, 0.0), 4.0),
+                (Site::new(2.0, 0.0), -2.0)
+            ]
+        );
+
+        // not changed by the invalid band
+        network.quantize_elevations(0.0);
+        assert_eq!(network.get_node(NodeId::new(0)).unwrap().elevation, 2.0);
+    }
+
+    #[test]
+    fn test_densify() {
+        let nodes = vec![
+            TransportNode::new(Site::new(0.0, 0.0), 0.0, Stage::default(), false),
+            TransportNode::new(Site::new(2.5, 0.0), 5.0, Stage::default(), false),
+            create_node(2.5, 0.5),
+        ];
+        let mut network = PathNetwork::from(nodes, &[(0, 1), (1, 2)]).unwrap();
+        network.densify(1.0);
+
+        let mut nodes = network
+            .nodes_iter()
+            .map(|(_, node)| *node)
+            .filter(|node| node.site.y == 0.0)
+            .collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(nodes.len(), 4);
+        for (i, node) in nodes.iter().enumerate() {
+            assert!((node.site.x - 2.5 * i as f64 / 3.0).abs() < 1e-9);
+            assert!((node.elevation - node.site.x * 2.0).abs() < 1e-9);
+        }
+        assert_eq!(network.paths_with_nodes().len(), 4);
+        assert!((network.total_length() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_collapse_collinear() {
+        let nodes = vec![
+            create_node(0.0, 0.0),
+            create_node(1.0, 0.0),
+            create_node(2.0, 0.01),
+            create_node(3.0, 0.0),
+            create_node(3.0, 1.0),
+            TransportNode::new(Site::new(3.0, 2.0), 0.0, Stage::default(), true),
+            create_node(3.0, 3.0),
+        ];
+        let mut network =
+            PathNetwork::from(nodes, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]).unwrap();
+        network.collapse_collinear(0.1);
+
+        // the corner and the nodes around the bridge are kept
+        let sites = network
+            .nodes_iter()
+            .map(|(_, node)| node.site)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sites,
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(3.0, 0.0),
+                Site::new(3.0, 1.0),
+                Site::new(3.0, 2.0),
+                Site::new(3.0, 3.0)
+            ]
+        );
+        assert!(network.has_path(NodeId::new(0), NodeId::new(3)));
+        assert_eq!(network.paths_with_nodes().len(), 4);
+
+        // the middle node of a triangle is not removed
+        let nodes = vec![
+            create_node(0.0, 0.0),
+            create_node(1.0, 0.1),
+            create_node(2.0, 0.0),
+        ];
+        let mut network = PathNetwork::from(nodes, &[(0, 1), (1, 2), (0, 2)]).unwrap();
+        network.collapse_collinear(1.0);
+        assert_eq!(network.nodes_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_simplify() {
+        // a zigzag between a dead end and a junction
+        let nodes = vec![
+            create_node(0.0, 0.0),
+            create_node(1.0, 0.1),
+            create_node(2.0, -0.1),
+            create_node(3.0, 1.0),
+            create_node(4.0, 0.0),
+            create_node(5.0, 1.0),
+            create_node(5.0, -1.0),
+        ];
+        let mut network =
+            PathNetwork::from(nodes, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (4, 6)]).unwrap();
+        network.simplify(0.5);
+
+        let sites = network
+            .nodes_iter()
+            .map(|(_, node)| node.site)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sites,
+            vec![
+                Site::new(0.0, 0.0),
+                Site::new(2.0, -0.1),
+                Site::new(3.0, 1.0),
+                Site::new(4.0, 0.0),
+                Site::new(5.0, 1.0),
+                Site::new(5.0, -1.0)
+            ]
+        );
+        assert!(network.has_path(NodeId::new(0), NodeId::new(2)));
+        assert_eq!(network.paths_with_nodes().len(), 5);
+    }
+
+    #[test]
+    fn test_lod_pyramid() {
+        let nodes = (0..9)
+            .map(|i| create_node(i as f64, if i % 2 == 0 { 0.0 } else { 0.3 }))
+            .chain([create_node(8.0, 5.0)])
+            .collect::<Vec<_>>();
+        let paths = (0..9).map(|i| (i, i + 1)).collect::<Vec<_>>();
+        let network = PathNetwork::from(nodes, &paths).unwrap();
+
+        let levels = network.lod_pyramid(&[0.1, 1.0, 10.0]);
+        let node_counts = levels
+            .iter()
+            .map(|level| level.nodes_iter().count())
+            .collect::<Vec<_>>();
+        assert_eq!(node_counts, vec![10, 3, 2]);
+        // the original network is not changed
+        assert_eq!(network.nodes_iter().count(), 10);
+    }
+
+    #[test]
+    fn test_relax_edge_lengths() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(create_node(0.0, 0.0));
+        let node1 = network.add_node(create_node(4.0, 0.0));
+        let node2 = network.add_node(create_node(4.2, 0.0));
+        let node3 = network.add_node(create_node(5.2, 0.0));
+        let node4 = network.add_node(create_node(5.2, 1.0));
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+        network.add_path(node2, node3);
+        network.add_path(node3, node4);
+
+        network.relax_edge_lengths(1.0, 10);
+
+        // the long path is split, and the short path is collapsed
+        network
+            .paths_with_nodes()
+            .iter()
+            .for_each(|((_, start), (_, end))| {
+                let length = start.site.distance(&end.site);
+                assert!((0.5..=1.5).contains(&length));
+            });
+        // endpoints and junctions are kept
+        assert!(network.contains(node0));
+        assert!(network.contains(node4));
+        assert!(!network.contains(node2));
+        assert_eq!(network.nodes_iter().count(), 7);
+    }
+
+    #[test]
+    fn test_jitter_straight_runs() {
+        struct AlternateRandom(bool);
+
+        impl RandomF64Provider for AlternateRandom {
+            fn gen_f64(&mut self) -> f64 {
+                self.0 = !self.0;
+                if self.0 {
+                    0.9
+                } else {
+                    0.1
+                }
+            }
+        }
+
+        // a straight chain (0 - 5) and a bent chain (5 - 8) from the junction 5
+        let nodes = [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 0.0),
+            (4.0, 0.0),
+            (5.0, 0.0),
+            (6.0, 0.0),
+            (7.0, 1.0),
+            (8.0, 1.0),
+            (5.0, 1.0),
+            (5.0, -1.0),
+        ]
+        .map(|(x, y)| create_node(x, y));
+        let paths = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 8),
+            (5, 9),
+            (5, 10),
+        ];
+        let original = PathNetwork::from_detailed(nodes.to_vec(), &paths).unwrap();
+
+        // the runs are not long enough
+        let mut network = original.clone();
+        network.jitter_straight_runs(5.0, 0.5, &mut AlternateRandom(false));
+        assert_eq!(
+            network.nodes_iter().collect::<Vec<_>>(),
+            original.nodes_iter().collect::<Vec<_>>()
+        );
+
+        let mut network = original.clone();
+        network.jitter_straight_runs(4.0, 0.5, &mut AlternateRandom(false));
+        let sites = network
+            .nodes_iter()
+            .map(|(_, node)| node.site)
+            .collect::<Vec<_>>();
+        // only the middle nodes of the straight chain are moved
+        for (i, site) in sites.iter().enumerate() {
+            if (1..=4).contains(&i) {
+                assert_eq!(site.x, nodes[i].site.x);
+                assert!((site.y.abs() - 0.4).abs() < 1e-9);
+            } else {
+                assert_eq!(*site, nodes[i].site);
+            }
+        }
+        // the paths are moved with the nodes
+        let crossings = network.crossings_with_segment(Site::new(1.5, -1.0), Site::new(1.5, 1.0));
+        assert_eq!(crossings.len(), 1);
+        let expected = sites[1].midpoint(&sites[2]);
+        assert!(crossings[0].1.distance(&expected) < 1e-9);
+    }
+
+    #[test]
+    fn test_elevation_profile() {
+        struct HillProvider;
+
+        impl TerrainProvider for HillProvider {
+            fn get_elevation(&self, site: &Site) -> Option<f64> {
+                if site.x > 3.5 {
+                    return None;
+                }
+                Some(4.0 - (site.x - 2.0).abs())
+            }
+        }
+
+        let network = PathNetwork::from_detailed(
+            vec![
+                create_node(0.0, 0.0),
+                create_node(4.0, 0.0),
+                create_node(0.0, 1.0),
+            ],
+            &[(0, 1)],
+        )
+        .unwrap();
+        let (start, end, isolated) = (NodeId::new(0), NodeId::new(1), NodeId::new(2));
+
+        assert_eq!(
+            network.elevation_profile(start, end, 5, &HillProvider),
+            vec![(0.0, 2.0), (1.0, 3.0), (2.0, 4.0), (3.0, 3.0)]
+        );
+        assert_eq!(
+            network.elevation_profile(end, start, 3, &HillProvider),
+            vec![(2.0, 4.0), (4.0, 2.0)]
+        );
+        assert!(network
+            .elevation_profile(start, isolated, 5, &HillProvider)
+            .is_empty());
+        assert!(network
+            .elevation_profile(start, end, 0, &HillProvider)
+            .is_empty());
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{core::geometry::site::Site, transport::params::numeric::Stage};
+use crate::{
+    core::{container::path_network::PathNetworkNodeTrait, geometry::site::Site},
+    transport::params::numeric::Stage,
+};
+
+/// Node which can be created by `TransportBuilder`.
+///
+/// The builder creates a `TransportNode` and converts it to this type by `From<TransportNode>`,
+/// so custom data (e.g. the district or the zoning) can be attached to the nodes depending on their attributes.
+/// The growth of the paths depends only on the `TransportNode` given by `transport_node`.
+pub trait TransportNodeTrait: PathNetworkNodeTrait + From<TransportNode> {
+    /// Get the attributes of the node which are used to grow the paths.
+    fn transport_node(&self) -> &TransportNode;
+}
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct TransportNode {
@@ -18,6 +31,14 @@ impl TransportNode {
         }
     }
 
+    pub fn with_elevation(self, elevation: f64) -> Self {
+        Self { elevation, ..self }
+    }
+
+    pub fn get_stage(&self) -> Stage {
+        self.stage
+    }
+
     pub fn path_creates_bridge(&self, other: &Self) -> bool {
         self.is_bridge || other.is_bridge
     }
@@ -36,6 +57,12 @@ impl TransportNode {
 
 impl Eq for TransportNode {}
 
+impl TransportNodeTrait for TransportNode {
+    fn transport_node(&self) -> &TransportNode {
+        self
+    }
+}
+
 impl From<TransportNode> for Site {
     fn from(node: TransportNode) -> Self {
         node.site
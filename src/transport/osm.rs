@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::core::container::path_network::PathNetwork;
+
+use super::node::TransportNode;
+
+/// Mean radius of the earth in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Get the value of the `highway` tag from the stage of the path.
+fn highway_tag(stage: usize) -> &'static str {
+    match stage {
+        0 => "primary",
+        1 => "secondary",
+        2 => "tertiary",
+        _ => "residential",
+    }
+}
+
+impl PathNetwork<TransportNode> {
+    /// Export the network as an OSM XML document.
+    ///
+    /// Sites are projected to latitude and longitude by the equirectangular projection around `origin_latlon`,
+    /// where the site (0, 0) is placed at `origin_latlon` and one unit of the site is `meters_per_unit` meters.
+    /// As the y-axis of sites points down (the angle 0 is the negative y-axis), the negative y direction is north.
+    ///
+    /// Each path is exported as a way with two nodes.
+    /// Ways are tagged with `highway` derived from the stage of the path
+    /// (0: `primary`, 1: `secondary`, 2: `tertiary`, otherwise `residential`), and `bridge=yes` for bridges.
+    /// Elevations are exported as the `ele` tag of nodes.
+    pub fn to_osm_xml(&self, origin_latlon: (f64, f64), meters_per_unit: f64) -> String {
+        let (lat0, lon0) = origin_latlon;
+        let meters_per_degree = EARTH_RADIUS * std::f64::consts::PI / 180.0;
+        let lon_scale = lat0.to_radians().cos();
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<osm version=\"0.6\" generator=\"street-engine\">\n");
+
+        // OSM ids should be positive
+        let mut osm_ids = BTreeMap::new();
+        self.nodes_iter().enumerate().for_each(|(i, (node_id, node))| {
+            let osm_id = i + 1;
+            osm_ids.insert(node_id, osm_id);
+            let lat = lat0 - node.site.y * meters_per_unit / meters_per_degree;
+            let lon = lon0 + node.site.x * meters_per_unit / (meters_per_degree * lon_scale);
+            let _ = writeln!(
+                xml,
+                "  <node id=\"{}\" version=\"1\" lat=\"{:.7}\" lon=\"{:.7}\">\n    <tag k=\"ele\" v=\"{}\"/>\n  </node>",
+                osm_id, lat, lon, node.elevation
+            );
+        });
+
+        let mut way_id = 0;
+        self.nodes_iter().for_each(|(node_id, node)| {
+            self.neighbors_iter(node_id)
+                .into_iter()
+                .flatten()
+                .filter(|(neighbor_id, _)| node_id < *neighbor_id)
+                .for_each(|(neighbor_id, neighbor)| {
+                    let (start, end) = if let (Some(start), Some(end)) =
+                        (osm_ids.get(&node_id), osm_ids.get(&neighbor_id))
+                    {
+                        (start, end)
+                    } else {
+                        return;
+                    };
+                    way_id += 1;
+                    let _ = writeln!(xml, "  <way id=\"{}\" version=\"1\">", way_id);
+                    let _ = writeln!(xml, "    <nd ref=\"{}\"/>", start);
+                    let _ = writeln!(xml, "    <nd ref=\"{}\"/>", end);
+                    let _ = writeln!(
+                        xml,
+                        "    <tag k=\"highway\" v=\"{}\"/>",
+                        highway_tag(node.path_stage(neighbor).as_num())
+                    );
+                    if node.path_creates_bridge(neighbor) {
+                        xml.push_str("    <tag k=\"bridge\" v=\"yes\"/>\n");
+                    }
+                    xml.push_str("  </way>\n");
+                });
+        });
+
+        xml.push_str("</osm>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core::geometry::site::Site, transport::params::numeric::Stage};
+
+    use super::*;
+
+    #[test]
+    fn test_to_osm_xml() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(TransportNode::new(
+            Site::new(0.0, 0.0),
+            1.0,
+            Stage::from_num(0),
+            false,
+        ));
+        let node1 = network.add_node(TransportNode::new(
+            Site::new(0.0, -1000.0),
+            2.0,
+            Stage::from_num(1),
+            true,
+        ));
+        network.add_path(node0, node1);
+
+        let xml = network.to_osm_xml((35.0, 139.0), 1.0);
+        assert!(
+            xml.contains("<node id=\"1\" version=\"1\" lat=\"35.0000000\" lon=\"139.0000000\">")
+        );
+        // 1000 meters to the north
+        assert!(xml.contains("lat=\"35.0089932\" lon=\"139.0000000\""));
+        assert!(xml.contains("<nd ref=\"1\"/>\n    <nd ref=\"2\"/>"));
+        assert!(xml.contains("<tag k=\"highway\" v=\"secondary\"/>"));
+        assert!(xml.contains("<tag k=\"bridge\" v=\"yes\"/>"));
+        assert!(xml.ends_with("</osm>\n"));
+    }
+}
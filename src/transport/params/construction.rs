@@ -0,0 +1,14 @@
+use super::{metrics::PathMetrics, numeric::Stage, rules::GrowthMode};
+
+/// Factors of the rules referenced when the path is created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathConstructionFactors {
+    /// The stage of the path.
+    pub stage: Stage,
+    /// The metrics of the path.
+    pub metrics: PathMetrics,
+    /// The normal length of the path in the rules.
+    pub path_normal_length: f64,
+    /// The mode of extending the path in the rules.
+    pub growth_mode: GrowthMode,
+}
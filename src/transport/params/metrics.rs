@@ -9,6 +9,8 @@ pub struct PathMetrics {
     pub extend_count_since_last_branched: usize,
     /// The number of times the path has been branched.
     pub branch_count: usize,
+    /// The number of consecutive switchback segments just before the path.
+    pub switchback_count: usize,
 }
 
 impl PathMetrics {
@@ -36,6 +38,15 @@ impl PathMetrics {
             extend_count_since_last_staged: count_last_staged,
             extend_count_since_last_branched: count_last_branched,
             branch_count,
+            switchback_count: 0,
+        }
+    }
+
+    /// Get the metrics for the path following a switchback segment.
+    pub fn switchback_incremented(&self) -> Self {
+        Self {
+            switchback_count: self.switchback_count + 1,
+            ..self.clone()
         }
     }
 }
@@ -1,4 +1,6 @@
+pub mod construction;
 pub mod metrics;
 pub mod numeric;
 pub mod priority;
 pub mod rules;
+pub mod style;
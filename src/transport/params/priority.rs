@@ -3,6 +3,7 @@ use crate::core::geometry::site::Site;
 use super::numeric::Stage;
 
 /// Factors for prioritizing the path.
+#[derive(Debug, Clone, PartialEq)]
 pub struct PathPrioritizationFactors {
     /// The start site of the path.
     pub site_start: Site,
@@ -14,4 +15,8 @@ pub struct PathPrioritizationFactors {
     pub stage: Stage,
     /// Whether the path is a bridge.
     pub creates_bridge: bool,
+    /// The sites sampled along the path (the midpoints of equally divided sections, from the start to the end).
+    ///
+    /// The number of sites is `TransportRules::path_priority_sample_count`, so this is empty by default.
+    pub sample_points: Vec<Site>,
 }
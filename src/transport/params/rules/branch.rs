@@ -8,6 +8,19 @@ pub struct BranchRules {
 
     /// Probability of staging.
     pub staging_probability: f64,
+
+    /// Length of the offset of the start of the branch from the branching node.
+    ///
+    /// If positive, the branch starts from a new node which is offset along the normal of the straight direction
+    /// of the path (on the side of the branch), so that branches do not start at the same site.
+    /// If 0.0, the branch starts at the branching node.
+    pub branch_offset: f64,
+
+    /// Probability of terminating the path at the new node (creating a dead end like a cul-de-sac).
+    ///
+    /// If the path is terminated, the path is not extended straight from the new node,
+    /// while branches can still be created from the node. If 0.0, the path is never terminated.
+    pub termination_probability: f64,
 }
 
 impl Default for BranchRules {
@@ -15,6 +28,8 @@ impl Default for BranchRules {
         Self {
             branch_density: 0.0,
             staging_probability: 0.0,
+            branch_offset: 0.0,
+            termination_probability: 0.0,
         }
     }
 }
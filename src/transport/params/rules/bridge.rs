@@ -10,15 +10,64 @@ pub struct BridgeRules {
     /// Maximum length of bridges.
     pub max_bridge_length: f64,
 
+    /// Minimum length of bridges.
+    ///
+    /// The lengths of bridges to check are `check_step` lengths evenly spaced from this value to `max_bridge_length`.
+    /// If 0.0, the shortest bridge to check is `max_bridge_length / check_step`.
+    pub min_bridge_length: f64,
+
     /// Number of check steps to create a bridge.
     pub check_step: usize,
 }
 
+impl BridgeRules {
+    /// Get the length of the bridge to check at the `step`-th step (1 to `check_step`).
+    pub(crate) fn bridge_length(&self, step: usize) -> f64 {
+        if self.check_step == 0 {
+            return 0.0;
+        }
+        let shortest = if self.min_bridge_length > 0.0 {
+            self.min_bridge_length
+        } else {
+            self.max_bridge_length / self.check_step as f64
+        };
+        if self.check_step == 1 {
+            return shortest;
+        }
+        shortest
+            + (self.max_bridge_length - shortest) * (step - 1) as f64 / (self.check_step - 1) as f64
+    }
+}
+
 impl Default for BridgeRules {
     fn default() -> Self {
         Self {
             max_bridge_length: 0.0,
+            min_bridge_length: 0.0,
             check_step: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bridge_length() {
+        let rules = BridgeRules {
+            max_bridge_length: 4.0,
+            check_step: 4,
+            ..Default::default()
+        };
+        let lengths = (1..=4).map(|i| rules.bridge_length(i)).collect::<Vec<_>>();
+        assert_eq!(lengths, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let rules = BridgeRules {
+            min_bridge_length: 0.25,
+            ..rules
+        };
+        let lengths = (1..=4).map(|i| rules.bridge_length(i)).collect::<Vec<_>>();
+        assert_eq!(lengths, vec![0.25, 1.5, 2.75, 4.0]);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::core::geometry::angle::Angle;
+
 /// Rules to determine the direction of a path.
 ///
 /// With `Default` values, the path is always constructed as a straight line.
@@ -8,6 +10,10 @@ pub struct PathDirectionRules {
     /// Number of candidates of the next site to create a path.
     /// This parameter should be an odd number to evaluate the straight path.
     pub comparison_step: usize,
+    /// Preference of the terrain to follow when choosing the next site among candidates.
+    pub terrain_following: TerrainFollowing,
+    /// Bias of the candidate directions toward the grid axes. If `None`, the directions are not biased.
+    pub grid_bias: Option<GridBias>,
 }
 
 impl Default for PathDirectionRules {
@@ -15,6 +21,63 @@ impl Default for PathDirectionRules {
         Self {
             max_radian: 0.0,
             comparison_step: 1,
+            terrain_following: TerrainFollowing::None,
+            grid_bias: None,
+        }
+    }
+}
+
+/// Bias of the directions of the paths toward the grid axes, to create grid-like (e.g. Manhattan-style) layouts.
+///
+/// Each candidate direction is rotated toward the nearest multiple of `base_radian` by the ratio of `pull`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridBias {
+    /// Angle between the grid axes (e.g. PI/2 for the square grid).
+    pub base_radian: f64,
+    /// Strength of the pull in [0, 1].
+    ///
+    /// If 1.0, the directions are snapped to the grid axes. If 0.0, the directions are not changed.
+    pub pull: f64,
+}
+
+impl GridBias {
+    /// Rotate the angle toward the nearest grid axis.
+    pub fn pull(&self, angle: Angle) -> Angle {
+        if self.base_radian.is_nan() || self.base_radian <= 0.0 {
+            return angle;
+        }
+        let radian = angle.radian();
+        let axis = (radian / self.base_radian).round() * self.base_radian;
+        Angle::new(radian + (axis - radian) * self.pull.clamp(0.0, 1.0))
+    }
+}
+
+/// Preference of the terrain to follow.
+///
+/// The slope of each candidate path (elevation difference per path length) multiplied by the weight
+/// is added to (or subtracted from) the priority of the candidate when choosing the direction.
+/// This does not affect the priority of the stump itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerrainFollowing {
+    /// Choose the candidate only by the priority.
+    None,
+    /// Prefer the candidate with smaller elevation change (following valleys or contours).
+    Valley(f64),
+    /// Prefer the candidate with larger elevation change (following ridges or climbing).
+    Ridge(f64),
+}
+
+impl TerrainFollowing {
+    /// Get the term to be added to the priority of the candidate.
+    pub fn evaluate(&self, elevations: (f64, f64), path_length: f64) -> f64 {
+        if path_length <= 0.0 {
+            return 0.0;
+        }
+        let slope = (elevations.1 - elevations.0).abs() / path_length;
+        match self {
+            TerrainFollowing::None => 0.0,
+            TerrainFollowing::Valley(weight) => -weight * slope,
+            TerrainFollowing::Ridge(weight) => weight * slope,
         }
     }
 }
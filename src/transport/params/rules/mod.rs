@@ -1,16 +1,23 @@
 use branch::BranchRules;
 use bridge::BridgeRules;
 use direction::PathDirectionRules;
+use switchback::SwitchbackRules;
 
 pub mod branch;
 pub mod bridge;
 pub mod direction;
+pub mod switchback;
 
 /// Rules to construct a path.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TransportRules {
     /// Normal length of the path.
     pub path_normal_length: f64,
+    /// Random variation of the normal length of the path.
+    ///
+    /// The normal length is multiplied by `1 + path_length_jitter * (2 * r - 1)` where `r` is a random value in [0, 1).
+    /// If 0.0, the length is always the normal length.
+    pub path_length_jitter: f64,
     /// Extra length of the path to search intersections.
     pub path_extra_length_for_intersection: f64,
 
@@ -25,34 +32,97 @@ pub struct TransportRules {
     /// Probability of branching. If 1.0, the path will always create branch.
     pub branch_rules: BranchRules,
 
+    /// If true, the path never creates branches regardless of `branch_rules`.
+    pub forbid_branch: bool,
+
     /// Rules to determine the direction of the path.
     pub path_direction_rules: PathDirectionRules,
 
     /// Rules to create bridges.
     pub bridge_rules: BridgeRules,
+
+    /// Rules to create switchbacks on steep slopes. If `None`, switchbacks are never created.
+    pub switchback_rules: Option<SwitchbackRules>,
+
+    /// Mode of extending the path.
+    pub growth_mode: GrowthMode,
+
+    /// Maximum number of paths connected to a node. If `None`, the number is not limited.
+    ///
+    /// The path is not connected to the existing node which already has this number of paths,
+    /// and does not create an intersection (which has 3 paths) if this value is less than 3.
+    pub max_node_degree: Option<usize>,
+
+    /// Minimum length of the path.
+    ///
+    /// If the next node (e.g. the intersection with an existing path) is closer to the start node than this value, the path is not created.
+    /// If 0.0, paths of any length are created.
+    pub min_edge_length: f64,
+
+    /// Minimum angle (in radian) between the path and the existing path which the path crosses to create an intersection.
+    ///
+    /// If the path crosses the existing path more shallowly than this value, the path is not created.
+    /// If 0.0, intersections are created at any angle.
+    pub min_intersection_angle: f64,
+
+    /// Number of sites sampled along the path for the prioritization.
+    ///
+    /// The sites are given to `PathPrioritizator` as `PathPrioritizationFactors::sample_points`.
+    /// If 0, no sites are sampled.
+    pub path_priority_sample_count: usize,
 }
 
 impl Default for TransportRules {
     fn default() -> Self {
         Self {
             path_normal_length: 0.0,
+            path_length_jitter: 0.0,
             path_extra_length_for_intersection: 0.0,
             path_slope_elevation_diff_limit: ElevationDiffLimit::AlwaysAllow,
             path_grade_separation_elevation_diff_threshold: 0.0,
             branch_rules: BranchRules::default(),
+            forbid_branch: false,
             path_direction_rules: PathDirectionRules::default(),
             bridge_rules: BridgeRules::default(),
+            switchback_rules: None,
+            growth_mode: GrowthMode::Normal,
+            max_node_degree: None,
+            min_edge_length: 0.0,
+            min_intersection_angle: 0.0,
+            path_priority_sample_count: 0,
         }
     }
 }
 
 impl TransportRules {
+    /// Check if the rules can be used to construct paths.
+    ///
+    /// The fatal errors (see `RulesError::is_fatal`) are reported before the others.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if self.path_normal_length.is_nan() || self.path_normal_length <= 0.0 {
+            return Err(RulesError::NonPositivePathLength(self.path_normal_length));
+        }
+        if self.path_direction_rules.comparison_step == 0 {
+            return Err(RulesError::ZeroComparisonStep);
+        }
+        if self.bridge_rules.max_bridge_length > 0.0 && self.bridge_rules.check_step == 0 {
+            return Err(RulesError::BridgeWithoutCheckStep);
+        }
+        Ok(())
+    }
+
     /// Set the normal length of the path.
     pub fn path_normal_length(mut self, path_normal_length: f64) -> Self {
         self.path_normal_length = path_normal_length;
         self
     }
 
+    /// Set the random variation of the normal length of the path.
+    pub fn path_length_jitter(mut self, path_length_jitter: f64) -> Self {
+        self.path_length_jitter = path_length_jitter;
+        self
+    }
+
     /// Set the extra length of the path to search intersections.
     pub fn path_extra_length_for_intersection(
         mut self,
@@ -87,6 +157,12 @@ impl TransportRules {
         self
     }
 
+    /// Set whether the path never creates branches.
+    pub fn forbid_branch(mut self, forbid_branch: bool) -> Self {
+        self.forbid_branch = forbid_branch;
+        self
+    }
+
     /// Set the rules to determine the direction of the path.
     pub fn path_direction_rules(mut self, path_direction_rules: PathDirectionRules) -> Self {
         self.path_direction_rules = path_direction_rules;
@@ -98,10 +174,100 @@ impl TransportRules {
         self.bridge_rules = bridge_rules;
         self
     }
+
+    /// Set the rules to create switchbacks.
+    pub fn switchback_rules(mut self, switchback_rules: SwitchbackRules) -> Self {
+        self.switchback_rules = Some(switchback_rules);
+        self
+    }
+
+    /// Set the mode of extending the path.
+    pub fn growth_mode(mut self, growth_mode: GrowthMode) -> Self {
+        self.growth_mode = growth_mode;
+        self
+    }
+
+    /// Set the maximum number of paths connected to a node.
+    pub fn max_node_degree(mut self, max_node_degree: usize) -> Self {
+        self.max_node_degree = Some(max_node_degree);
+        self
+    }
+
+    /// Set the minimum length of the path.
+    pub fn min_edge_length(mut self, min_edge_length: f64) -> Self {
+        self.min_edge_length = min_edge_length;
+        self
+    }
+
+    /// Set the minimum angle between the path and the existing path to create an intersection.
+    pub fn min_intersection_angle(mut self, min_intersection_angle: f64) -> Self {
+        self.min_intersection_angle = min_intersection_angle;
+        self
+    }
+
+    /// Set the number of sites sampled along the path for the prioritization.
+    pub fn path_priority_sample_count(mut self, path_priority_sample_count: usize) -> Self {
+        self.path_priority_sample_count = path_priority_sample_count;
+        self
+    }
+}
+
+/// Error of the rules which cannot be used to construct paths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RulesError {
+    /// `path_normal_length` is not positive.
+    NonPositivePathLength(f64),
+    /// `path_direction_rules.comparison_step` is 0, so no direction is evaluated.
+    ZeroComparisonStep,
+    /// `bridge_rules.max_bridge_length` is positive but `bridge_rules.check_step` is 0, so bridges are never created.
+    BridgeWithoutCheckStep,
+}
+
+impl RulesError {
+    /// Whether no paths can be constructed from the rules.
+    ///
+    /// `BridgeWithoutCheckStep` is not fatal since the paths are still constructed without bridges.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, RulesError::BridgeWithoutCheckStep)
+    }
+}
+
+impl std::fmt::Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesError::NonPositivePathLength(length) => {
+                write!(f, "path_normal_length must be positive (got {})", length)
+            }
+            RulesError::ZeroComparisonStep => {
+                write!(f, "path_direction_rules.comparison_step must be at least 1")
+            }
+            RulesError::BridgeWithoutCheckStep => write!(
+                f,
+                "bridge_rules.check_step must be at least 1 if max_bridge_length is positive"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// Mode of extending the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrowthMode {
+    /// Extend the path by one segment for each iteration, searching curves and creating branches.
+    #[default]
+    Normal,
+    /// Extend the path straight without searching curves and creating branches,
+    /// until it is blocked by the terrain, the rules, or other paths.
+    ///
+    /// All segments are created in one iteration.
+    /// Note that the extension never stops if the path is not blocked at all.
+    StraightUntilBlocked,
 }
 
 /// The limit of the elevation difference.
 #[derive(Debug, Clone, PartialEq)]
+#[allow(unknown_lints, unpredictable_function_pointer_comparisons)]
 pub enum ElevationDiffLimit {
     /// Always allow to construct a path.
     AlwaysAllow,
@@ -111,22 +277,79 @@ pub enum ElevationDiffLimit {
     Linear(f64),
     /// The limit will be a non-linear function of the path length.
     NonLinear(fn(path_length: f64) -> f64),
+    /// The limit will be a non-linear function of the path length and the elevation of the start of the path.
+    NonLinearWithElevation(fn(path_length: f64, elevation: f64) -> f64),
 }
 
 impl ElevationDiffLimit {
-    /// Get the elevation difference from the path length.
-    fn value(&self, path_length: f64) -> f64 {
+    /// Get the elevation difference from the path length and the elevation of the start of the path.
+    fn value(&self, path_length: f64, elevation_start: f64) -> f64 {
         match self {
             ElevationDiffLimit::AlwaysAllow => f64::INFINITY,
             ElevationDiffLimit::AlwaysDeny => f64::NEG_INFINITY,
             ElevationDiffLimit::Linear(elevation) => elevation * path_length,
             ElevationDiffLimit::NonLinear(f) => f(path_length),
+            ElevationDiffLimit::NonLinearWithElevation(f) => f(path_length, elevation_start),
         }
     }
 
     /// Check if the slope is proper to construct a path.
+    ///
+    /// `elevations` is the pair of the elevations of the start and the end of the path.
     pub fn check_slope(&self, elevations: (f64, f64), path_length: f64) -> bool {
         let elevation_diff = (elevations.1 - elevations.0).abs();
-        elevation_diff <= self.value(path_length)
+        elevation_diff <= self.value(path_length, elevations.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let rules = TransportRules::default().path_normal_length(1.0);
+        assert_eq!(rules.validate(), Ok(()));
+
+        assert_eq!(
+            rules.clone().path_normal_length(0.0).validate(),
+            Err(RulesError::NonPositivePathLength(0.0))
+        );
+        assert!(rules
+            .clone()
+            .path_normal_length(f64::NAN)
+            .validate()
+            .is_err());
+
+        let zero_step = rules.clone().path_direction_rules(PathDirectionRules {
+            comparison_step: 0,
+            ..Default::default()
+        });
+        assert_eq!(zero_step.validate(), Err(RulesError::ZeroComparisonStep));
+
+        let bridge_without_step = rules.clone().bridge_rules(BridgeRules {
+            max_bridge_length: 1.0,
+            ..Default::default()
+        });
+        assert_eq!(
+            bridge_without_step.validate(),
+            Err(RulesError::BridgeWithoutCheckStep)
+        );
+        assert!(!RulesError::BridgeWithoutCheckStep.is_fatal());
+        assert!(RulesError::ZeroComparisonStep.is_fatal());
+    }
+
+    #[test]
+    fn test_check_slope() {
+        let limit = ElevationDiffLimit::Linear(0.5);
+        assert!(limit.check_slope((0.0, 1.0), 2.0));
+        assert!(!limit.check_slope((0.0, 1.5), 2.0));
+
+        // the limit is loosened at high altitude
+        let limit = ElevationDiffLimit::NonLinearWithElevation(|length, elevation| {
+            length * (0.5 + elevation * 0.01)
+        });
+        assert!(!limit.check_slope((0.0, 1.5), 2.0));
+        assert!(limit.check_slope((100.0, 101.5), 2.0));
     }
 }
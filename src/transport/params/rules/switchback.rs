@@ -0,0 +1,22 @@
+/// Rules to create switchbacks.
+///
+/// Switchback is the zig-zag path to climb a slope which is too steep to climb straight.
+/// When no candidate of the next site satisfies the slope limit, the path is extended
+/// at an angle of `turn_radian` from the expected direction, alternating the side for each segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchbackRules {
+    /// Angle between the expected direction and a switchback segment.
+    pub turn_radian: f64,
+
+    /// Maximum number of consecutive switchback segments.
+    pub max_segments: usize,
+}
+
+impl Default for SwitchbackRules {
+    fn default() -> Self {
+        Self {
+            turn_radian: std::f64::consts::PI / 3.0,
+            max_segments: 8,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/// Style to draw the paths of a stage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStyle {
+    /// Width of the path.
+    pub width: f64,
+    /// Tag to classify the path (e.g. "highway", "street").
+    pub tag: String,
+}
+
+impl StageStyle {
+    /// Create a new `StageStyle`.
+    pub fn new(width: f64, tag: impl Into<String>) -> Self {
+        Self {
+            width,
+            tag: tag.into(),
+        }
+    }
+}
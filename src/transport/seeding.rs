@@ -0,0 +1,235 @@
+use crate::core::geometry::site::Site;
+
+use super::traits::{RandomF64Provider, TerrainProvider};
+
+/// Number of candidates generated around an active site before it is deactivated.
+const CANDIDATES_PER_SITE: usize = 30;
+
+/// Generate well-distributed sites in the rectangle by Poisson-disk sampling (Bridson's algorithm).
+///
+/// Every pair of the returned sites is at least `min_distance` apart,
+/// and no more sites can be added in the rectangle in most cases.
+/// The returned sites can be used as origins of the network (see `TransportBuilder::add_origin`).
+///
+/// `rng` is expected to provide values in the range of [0, 1).
+/// If the rectangle is empty or not finite, or `min_distance` is not positive and finite (e.g. NaN or infinity),
+/// an empty vector is returned.
+pub fn poisson_disk_sites<R>(bounds: (Site, Site), min_distance: f64, rng: &mut R) -> Vec<Site>
+where
+    R: RandomF64Provider,
+{
+    sample_sites(bounds, min_distance, rng, |_| true)
+}
+
+/// Generate well-distributed sites on the buildable terrain by Poisson-disk sampling.
+///
+/// This is the same as `poisson_disk_sites`, but candidates where `terrain_provider` returns no elevation
+/// (e.g. over the sea) are skipped and retried.
+/// When the sampling cannot extend any more (e.g. the land is divided by the sea),
+/// it is restarted from a random buildable site in the rectangle, so that separated lands are also covered.
+pub fn poisson_disk_sites_on_terrain<R, TP>(
+    bounds: (Site, Site),
+    min_distance: f64,
+    terrain_provider: &TP,
+    rng: &mut R,
+) -> Vec<Site>
+where
+    R: RandomF64Provider,
+    TP: TerrainProvider,
+{
+    sample_sites(bounds, min_distance, rng, |site| {
+        terrain_provider.get_elevation(site).is_some()
+    })
+}
+
+/// Poisson-disk sampling of sites which satisfy `is_acceptable`.
+fn sample_sites<R, F>(
+    bounds: (Site, Site),
+    min_distance: f64,
+    rng: &mut R,
+    is_acceptable: F,
+) -> Vec<Site>
+where
+    R: RandomF64Provider,
+    F: Fn(&Site) -> bool,
+{
+    let (corner_0, corner_1) = bounds;
+    let (min_x, max_x) = (corner_0.x.min(corner_1.x), corner_0.x.max(corner_1.x));
+    let (min_y, max_y) = (corner_0.y.min(corner_1.y), corner_0.y.max(corner_1.y));
+    let is_finite = |site: &Site| site.x.is_finite() && site.y.is_finite();
+    if !min_distance.is_finite()
+        || min_distance <= 0.0
+        || !is_finite(&corner_0)
+        || !is_finite(&corner_1)
+        || min_x >= max_x
+        || min_y >= max_y
+    {
+        return Vec::new();
+    }
+
+    // Each cell of the grid contains at most one site.
+    let cell_size = min_distance / 2.0_f64.sqrt();
+    let grid_width = ((max_x - min_x) / cell_size).ceil() as usize;
+    let grid_height = ((max_y - min_y) / cell_size).ceil() as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; grid_width * grid_height];
+    let cell_of = |site: &Site| -> (usize, usize) {
+        let cx = (((site.x - min_x) / cell_size) as usize).min(grid_width - 1);
+        let cy = (((site.y - min_y) / cell_size) as usize).min(grid_height - 1);
+        (cx, cy)
+    };
+
+    // Check if the candidate is at least `min_distance` apart from the existing sites.
+    let is_far = |grid: &[Option<usize>], sites: &[Site], candidate: &Site| -> bool {
+        let (cx, cy) = cell_of(candidate);
+        (cy.saturating_sub(2)..(cy + 3).min(grid_height)).all(|y| {
+            (cx.saturating_sub(2)..(cx + 3).min(grid_width)).all(|x| {
+                grid[y * grid_width + x]
+                    .is_none_or(|index| sites[index].distance(candidate) >= min_distance)
+            })
+        })
+    };
+
+    let mut sites: Vec<Site> = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+
+    loop {
+        if active.is_empty() {
+            // (Re)start the sampling from a random site which is far from the existing sites.
+            let found = (0..CANDIDATES_PER_SITE).find_map(|_| {
+                let candidate = Site::new(
+                    min_x + (max_x - min_x) * rng.gen_f64(),
+                    min_y + (max_y - min_y) * rng.gen_f64(),
+                );
+                if is_acceptable(&candidate) && is_far(&grid, &sites, &candidate) {
+                    Some(candidate)
+                } else {
+                    None
+                }
+            });
+            if let Some(candidate) = found {
+                let (cx, cy) = cell_of(&candidate);
+                grid[cy * grid_width + cx] = Some(sites.len());
+                active.push(sites.len());
+                sites.push(candidate);
+            } else {
+                break;
+            }
+        }
+
+        let active_index = ((rng.gen_f64() * active.len() as f64) as usize).min(active.len() - 1);
+        let center = sites[active[active_index]];
+
+        let found = (0..CANDIDATES_PER_SITE).find_map(|_| {
+            // a candidate in the annulus between `min_distance` and `2 * min_distance`
+            let radian = rng.gen_f64() * 2.0 * std::f64::consts::PI;
+            let distance = min_distance * (1.0 + rng.gen_f64());
+            let candidate = Site::new(
+                center.x + radian.cos() * distance,
+                center.y + radian.sin() * distance,
+            );
+            if candidate.x < min_x
+                || candidate.x > max_x
+                || candidate.y < min_y
+                || candidate.y > max_y
+            {
+                return None;
+            }
+
+            if !is_acceptable(&candidate) || !is_far(&grid, &sites, &candidate) {
+                return None;
+            }
+            let (cx, cy) = cell_of(&candidate);
+            Some((candidate, cx, cy))
+        });
+
+        if let Some((candidate, cx, cy)) = found {
+            grid[cy * grid_width + cx] = Some(sites.len());
+            active.push(sites.len());
+            sites.push(candidate);
+        } else {
+            active.swap_remove(active_index);
+        }
+    }
+
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct XorShift(u64);
+
+    impl RandomF64Provider for XorShift {
+        fn gen_f64(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    #[test]
+    fn test_poisson_disk_sites() {
+        let bounds = (Site::new(-10.0, -5.0), Site::new(10.0, 5.0));
+        let min_distance = 1.5;
+        let sites = poisson_disk_sites(bounds, min_distance, &mut XorShift(42));
+
+        assert!(sites.len() > 20);
+        for (i, site_i) in sites.iter().enumerate() {
+            assert!((-10.0..=10.0).contains(&site_i.x) && (-5.0..=5.0).contains(&site_i.y));
+            for site_j in sites.iter().skip(i + 1) {
+                assert!(site_i.distance(site_j) >= min_distance);
+            }
+        }
+
+        assert!(poisson_disk_sites(bounds, 0.0, &mut XorShift(42)).is_empty());
+    }
+
+    #[test]
+    fn test_poisson_disk_sites_not_finite() {
+        let bounds = (Site::new(-10.0, -5.0), Site::new(10.0, 5.0));
+        for min_distance in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(poisson_disk_sites(bounds, min_distance, &mut XorShift(42)).is_empty());
+        }
+        for corner in [
+            Site::new(f64::NAN, 5.0),
+            Site::new(10.0, f64::INFINITY),
+            Site::new(f64::NEG_INFINITY, 5.0),
+        ] {
+            let bounds = (Site::new(-10.0, -5.0), corner);
+            assert!(poisson_disk_sites(bounds, 1.5, &mut XorShift(42)).is_empty());
+        }
+    }
+
+    struct IslandTerrain;
+
+    impl TerrainProvider for IslandTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            // two islands separated by the sea
+            if site.x.abs() < 3.0 {
+                None
+            } else {
+                Some(1.0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_poisson_disk_sites_on_terrain() {
+        let bounds = (Site::new(-10.0, -5.0), Site::new(10.0, 5.0));
+        let min_distance = 1.5;
+        let sites =
+            poisson_disk_sites_on_terrain(bounds, min_distance, &IslandTerrain, &mut XorShift(7));
+
+        assert!(sites.iter().all(|site| site.x.abs() >= 3.0));
+        // both islands are covered
+        assert!(sites.iter().any(|site| site.x < 0.0));
+        assert!(sites.iter().any(|site| site.x > 0.0));
+        for (i, site_i) in sites.iter().enumerate() {
+            for site_j in sites.iter().skip(i + 1) {
+                assert!(site_i.distance(site_j) >= min_distance);
+            }
+        }
+    }
+}
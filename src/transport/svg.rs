@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use crate::core::{container::path_network::PathNetwork, geometry::site::Site};
+
+use super::node::TransportNode;
+
+/// Stroke color of bridges.
+const BRIDGE_COLOR: &str = "rgb(0,230,240)";
+/// Stroke color of paths other than bridges.
+const PATH_COLOR: &str = "rgb(0,0,0)";
+
+/// Render the network as a standalone SVG document.
+///
+/// The area of `bounds` (the minimum and maximum corners) is scaled into the viewport of `width` x `height`.
+/// Each path is drawn as a `<line>`. Bridges are drawn in cyan, and paths of stage 0 are drawn thicker than others.
+/// The output is deterministic for the same network.
+pub fn to_svg(
+    network: &PathNetwork<TransportNode>,
+    bounds: (Site, Site),
+    width: u32,
+    height: u32,
+) -> String {
+    let (bound_min, bound_max) = bounds;
+    let to_viewport = |site: Site| {
+        (
+            (site.x - bound_min.x) / (bound_max.x - bound_min.x) * width as f64,
+            (site.y - bound_min.y) / (bound_max.y - bound_min.y) * height as f64,
+        )
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        width, height
+    );
+    network.nodes_iter().for_each(|(node_id, node)| {
+        network
+            .neighbors_iter(node_id)
+            .into_iter()
+            .flatten()
+            .filter(|(neighbor_id, _)| node_id < *neighbor_id)
+            .for_each(|(_, neighbor)| {
+                let stroke_width = if node.path_stage(neighbor).as_num() == 0 {
+                    2.0
+                } else {
+                    0.8
+                };
+                let color = if node.path_creates_bridge(neighbor) {
+                    BRIDGE_COLOR
+                } else {
+                    PATH_COLOR
+                };
+                let (x1, y1) = to_viewport(node.site);
+                let (x2, y2) = to_viewport(neighbor.site);
+                let _ = writeln!(
+                    svg,
+                    "  <line x1=\"{:.3}\" y1=\"{:.3}\" x2=\"{:.3}\" y2=\"{:.3}\" stroke=\"{}\" stroke-width=\"{}\" stroke-opacity=\"0.4\" stroke-linecap=\"round\"/>",
+                    x1, y1, x2, y2, color, stroke_width
+                );
+            });
+    });
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::params::numeric::Stage;
+
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(TransportNode::new(
+            Site::new(0.0, 0.0),
+            0.0,
+            Stage::from_num(0),
+            false,
+        ));
+        let node1 = network.add_node(TransportNode::new(
+            Site::new(5.0, 5.0),
+            0.0,
+            Stage::from_num(0),
+            false,
+        ));
+        let node2 = network.add_node(TransportNode::new(
+            Site::new(10.0, 5.0),
+            0.0,
+            Stage::from_num(1),
+            true,
+        ));
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+
+        let svg = to_svg(
+            &network,
+            (Site::new(0.0, 0.0), Site::new(10.0, 10.0)),
+            200,
+            100,
+        );
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"100\" viewBox=\"0 0 200 100\">"
+        ));
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains(
+            "x1=\"0.000\" y1=\"0.000\" x2=\"100.000\" y2=\"50.000\" stroke=\"rgb(0,0,0)\" stroke-width=\"2\""
+        ));
+        assert!(svg.contains(
+            "x1=\"100.000\" y1=\"50.000\" x2=\"200.000\" y2=\"50.000\" stroke=\"rgb(0,230,240)\" stroke-width=\"0.8\""
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(
+            svg,
+            to_svg(
+                &network,
+                (Site::new(0.0, 0.0), Site::new(10.0, 10.0)),
+                200,
+                100
+            )
+        );
+    }
+}
@@ -14,17 +14,176 @@ pub trait TransportRulesProvider {
 /// Provider of terrain elevation.
 pub trait TerrainProvider {
     fn get_elevation(&self, site: &Site) -> Option<f64>;
+
+    /// Calculate the slope (the elevation difference per distance) from `a` to `b`.
+    ///
+    /// The slope is positive if `b` is higher than `a`.
+    /// Return `None` if either site has no elevation or the sites are the same.
+    fn slope_between(&self, a: Site, b: Site) -> Option<f64> {
+        let distance = a.distance(&b);
+        if distance == 0.0 {
+            return None;
+        }
+        Some((self.get_elevation(&b)? - self.get_elevation(&a)?) / distance)
+    }
+
+    /// Get the gradient of the elevation at the site, as the partial derivatives along the x and y axes.
+    ///
+    /// By default, the gradient is calculated by the central differences of `get_elevation`
+    /// at the distance of `GRADIENT_STEP`. Providers with analytic or precomputed gradients can override this.
+    /// Return `None` if any of the sampled sites has no elevation.
+    fn get_gradient(&self, site: &Site) -> Option<(f64, f64)> {
+        let step = GRADIENT_STEP;
+        let dx = self.get_elevation(&Site::new(site.x + step, site.y))?
+            - self.get_elevation(&Site::new(site.x - step, site.y))?;
+        let dy = self.get_elevation(&Site::new(site.x, site.y + step))?
+            - self.get_elevation(&Site::new(site.x, site.y - step))?;
+        Some((dx / (2.0 * step), dy / (2.0 * step)))
+    }
 }
 
+/// Distance between the sites sampled by the default `TerrainProvider::get_gradient`.
+pub const GRADIENT_STEP: f64 = 1e-3;
+
 /// Prioritizator of path.
 pub trait PathPrioritizator {
     /// Calculate the priority of the path from the start node and the expected path.
     fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64>;
 }
 
-/// Provider of random f64 values.
+/// Prioritizator which sums the weighted priorities of multiple prioritizators.
 ///
-/// The range of the value is the same as the range of `f64` (not constrained).
+/// If any of the prioritizators returns `None`, the path is rejected.
+/// If there are no prioritizators, the priority is always 0.0.
+#[derive(Default)]
+pub struct CompositePrioritizator<'a> {
+    /// Pairs of the prioritizator and the weight of its priority.
+    pub terms: Vec<(Box<dyn PathPrioritizator + 'a>, f64)>,
+}
+
+impl<'a> CompositePrioritizator<'a> {
+    /// Create a new `CompositePrioritizator` without prioritizators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a prioritizator with the weight of its priority.
+    pub fn term<P>(mut self, prioritizator: P, weight: f64) -> Self
+    where
+        P: PathPrioritizator + 'a,
+    {
+        self.terms.push((Box::new(prioritizator), weight));
+        self
+    }
+}
+
+impl PathPrioritizator for CompositePrioritizator<'_> {
+    fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+        self.terms
+            .iter()
+            .try_fold(0.0, |sum, (prioritizator, weight)| {
+                Some(sum + prioritizator.prioritize(factors.clone())? * weight)
+            })
+    }
+}
+
+/// Provider of random f64 values.
 pub trait RandomF64Provider {
+    /// Generate a random value in [0, 1).
+    ///
+    /// The value is compared with probabilities (e.g. `branch_density`), so values outside the range
+    /// break the probabilities. The builder checks the range in debug builds.
     fn gen_f64(&mut self) -> f64;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SlopeTerrain;
+
+    impl TerrainProvider for SlopeTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            if site.x < 0.0 {
+                None
+            } else {
+                Some(site.x * 0.5)
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_gradient() {
+        let terrain = SlopeTerrain;
+        let (dx, dy) = terrain.get_gradient(&Site::new(2.0, 1.0)).unwrap();
+        assert!((dx - 0.5).abs() < 1e-9);
+        assert!(dy.abs() < 1e-9);
+        // the terrain is missing on the west side
+        assert_eq!(terrain.get_gradient(&Site::new(0.0, 0.0)), None);
+    }
+
+    struct LengthPrioritizator;
+
+    impl PathPrioritizator for LengthPrioritizator {
+        fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+            Some(factors.path_length)
+        }
+    }
+
+    struct BridgeVeto;
+
+    impl PathPrioritizator for BridgeVeto {
+        fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+            if factors.creates_bridge {
+                None
+            } else {
+                Some(1.0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_composite_prioritizator() {
+        let factors = |creates_bridge: bool| PathPrioritizationFactors {
+            site_start: Site::new(0.0, 0.0),
+            site_end: Site::new(2.0, 0.0),
+            path_length: 2.0,
+            stage: Stage::default(),
+            creates_bridge,
+            sample_points: vec![],
+        };
+
+        let composite = CompositePrioritizator::new()
+            .term(LengthPrioritizator, 0.5)
+            .term(BridgeVeto, -3.0);
+        assert_eq!(composite.prioritize(factors(false)), Some(-2.0));
+        // vetoed by the bridge term
+        assert_eq!(composite.prioritize(factors(true)), None);
+
+        assert_eq!(
+            CompositePrioritizator::new().prioritize(factors(true)),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_slope_between() {
+        let terrain = SlopeTerrain;
+        assert_eq!(
+            terrain.slope_between(Site::new(0.0, 0.0), Site::new(4.0, 3.0)),
+            Some(0.4)
+        );
+        assert_eq!(
+            terrain.slope_between(Site::new(4.0, 0.0), Site::new(0.0, 0.0)),
+            Some(-0.5)
+        );
+        assert_eq!(
+            terrain.slope_between(Site::new(1.0, 0.0), Site::new(1.0, 0.0)),
+            None
+        );
+        assert_eq!(
+            terrain.slope_between(Site::new(-1.0, 0.0), Site::new(1.0, 0.0)),
+            None
+        );
+    }
+}